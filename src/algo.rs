@@ -1,12 +1,448 @@
+use crate::optimizer;
 use serde::{Serialize, Deserialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum AlgoConf {
-    ParallelHillClimbing(ParallelHillClimbingConf)
+    ParallelHillClimbing(ParallelHillClimbingConf),
+    SimulatedAnnealing(SimulatedAnnealingConf),
+    CmaEs(CmaEsConf),
+    DifferentialEvolution(DifferentialEvolutionConf),
+    Genetic(GeneticConf),
+    ParticleSwarm(ParticleSwarmConf),
+    NelderMead(NelderMeadConf),
+    BayesianOptimization(BayesianOptimizationConf),
+    Tpe(TpeConf),
+    NsgaII(NsgaIIConf),
+    Hyperband(HyperbandConf),
+    GridSearch(GridSearchConf),
+    RandomSearch(RandomSearchConf),
+    PatternSearch(PatternSearchConf),
+    TabuSearch(TabuSearchConf),
+    Custom(CustomConf),
+    IslandModel(IslandModelConf),
+}
+
+impl AlgoConf {
+    pub fn degree_of_par_mut(&mut self) -> &mut usize {
+        match self {
+            AlgoConf::ParallelHillClimbing(ParallelHillClimbingConf { degree_of_par, .. })
+            | AlgoConf::SimulatedAnnealing(SimulatedAnnealingConf { degree_of_par, .. })
+            | AlgoConf::CmaEs(CmaEsConf { degree_of_par, .. })
+            | AlgoConf::DifferentialEvolution(DifferentialEvolutionConf { degree_of_par, .. })
+            | AlgoConf::Genetic(GeneticConf { degree_of_par, .. })
+            | AlgoConf::ParticleSwarm(ParticleSwarmConf { degree_of_par, .. })
+            | AlgoConf::NelderMead(NelderMeadConf { degree_of_par, .. })
+            | AlgoConf::BayesianOptimization(BayesianOptimizationConf { degree_of_par, .. })
+            | AlgoConf::Tpe(TpeConf { degree_of_par, .. })
+            | AlgoConf::NsgaII(NsgaIIConf { degree_of_par, .. })
+            | AlgoConf::Hyperband(HyperbandConf { degree_of_par, .. })
+            | AlgoConf::GridSearch(GridSearchConf { degree_of_par, .. })
+            | AlgoConf::RandomSearch(RandomSearchConf { degree_of_par, .. })
+            | AlgoConf::PatternSearch(PatternSearchConf { degree_of_par, .. })
+            | AlgoConf::TabuSearch(TabuSearchConf { degree_of_par, .. })
+            | AlgoConf::Custom(CustomConf { degree_of_par, .. })
+            | AlgoConf::IslandModel(IslandModelConf { degree_of_par, .. }) => degree_of_par,
+        }
+    }
+
+    /// Checks the constraints documented on the confs, which the algorithms rely on
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            AlgoConf::ParticleSwarm(particle_swarm_conf) => particle_swarm_conf.validate(),
+            AlgoConf::Hyperband(hyperband_conf) => hyperband_conf.validate(),
+            AlgoConf::GridSearch(grid_search_conf) => grid_search_conf.validate(),
+            AlgoConf::Custom(custom_conf) => custom_conf.validate(),
+            AlgoConf::IslandModel(island_model_conf) => island_model_conf.validate(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the running algorithm of this conf can apply the update, see
+    /// `RequestMessage::UpdateAlgoConf`. Only parallel hill climbing supports updates so far.
+    pub fn supports_update_to(&self, update: &AlgoConf) -> bool {
+        matches!(
+            (self, update),
+            (
+                AlgoConf::ParallelHillClimbing(_),
+                AlgoConf::ParallelHillClimbing(_)
+            )
+        )
+    }
+}
+
+/// How evaluations are scheduled onto the `degree_of_par` slots
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Scheduling {
+    /// All candidates of an iteration are evaluated before the next iteration starts
+    #[default]
+    Generational,
+    /// A new candidate is generated as soon as any evaluation completes, keeping exactly
+    /// `degree_of_par` evaluations in flight
+    SteadyState,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ParallelHillClimbingConf {
     pub relative_std_dev: f64,
-    pub degree_of_par: usize
+    pub degree_of_par: usize,
+    #[serde(default)]
+    pub scheduling: Scheduling,
+    #[serde(default)]
+    pub step_size_adaptation: Option<StepSizeAdaptationConf>,
+    #[serde(default)]
+    pub restart: Option<RestartConf>,
+}
+
+/// Adapts `relative_std_dev` based on the success rate of the last `window_size` evaluations,
+/// where success means improving on the best objective function value since the last restart.
+/// The step size is multiplied by `adaptation_factor` if the success rate is above the target and
+/// divided by it if below. A target success rate of 0.2 yields the 1/5 success rule.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StepSizeAdaptationConf {
+    pub target_success_rate: f64,
+    pub adaptation_factor: f64,
+    pub window_size: usize,
+}
+
+/// Restarts the search from a uniformly random point once `evaluations_without_improvement`
+/// consecutive evaluations have not improved on the best candidate since the last restart. The
+/// best seen candidate of the job is retained. With a `population_growth_factor` above 1, the
+/// number of candidates per iteration grows by that factor on every restart (IPOP), while at most
+/// `degree_of_par` evaluations remain in flight. Population growth does not apply to steady-state
+/// scheduling.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RestartConf {
+    pub evaluations_without_improvement: usize,
+    pub population_growth_factor: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SimulatedAnnealingConf {
+    pub relative_std_dev: f64,
+    pub degree_of_par: usize,
+    pub initial_temperature: f64,
+    pub cooling_schedule: CoolingSchedule,
+    pub reheating: Option<ReheatingConf>,
+    #[serde(default)]
+    pub scheduling: Scheduling,
+}
+
+/// Temperature as a function of the iteration number k since the last (re)start of the schedule,
+/// where T0 is the temperature at the start of the schedule.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum CoolingSchedule {
+    /// T = T0 * decay_factor^k
+    Exponential { decay_factor: f64 },
+    /// T = max(T0 - decrement * k, 0)
+    Linear { decrement: f64 },
+    /// T = T0 / (1 + ln(1 + k))
+    Logarithmic,
+}
+
+/// Raises the temperature again once the best seen objective function value has not improved
+/// for a number of iterations.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReheatingConf {
+    pub iterations_without_improvement: usize,
+    pub temperature_fraction: f64,
+}
+
+/// The population size of each generation equals `degree_of_par` (at least two), unless grown by
+/// restarts. Step sizes are relative to the bounds of each dim.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CmaEsConf {
+    pub initial_relative_std_dev: f64,
+    pub degree_of_par: usize,
+    #[serde(default)]
+    pub restart: Option<RestartConf>,
+}
+
+/// The population size equals `degree_of_par`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DifferentialEvolutionConf {
+    /// Often denoted F
+    pub differential_weight: f64,
+    /// Often denoted CR
+    pub crossover_prob: f64,
+    pub strategy: DifferentialEvolutionStrategy,
+    pub degree_of_par: usize,
+    #[serde(default)]
+    pub scheduling: Scheduling,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum DifferentialEvolutionStrategy {
+    Rand1Bin,
+    Best1Bin,
+    CurrentToBest1Bin,
+}
+
+/// The population size equals `degree_of_par`. Offspring are mutated like hill climbing
+/// candidates, using `relative_std_dev`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GeneticConf {
+    pub relative_std_dev: f64,
+    pub degree_of_par: usize,
+    pub tournament_size: usize,
+    pub crossover_prob: f64,
+    pub crossover: CrossoverConf,
+    pub num_elites: usize,
+    #[serde(default)]
+    pub scheduling: Scheduling,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CrossoverConf {
+    pub real: RealCrossover,
+    pub integer: DiscreteCrossover,
+    pub boolean: DiscreteCrossover,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum RealCrossover {
+    Uniform,
+    Sbx { distribution_index: f64 },
+}
+
+/// One-point crossover cuts the sequence of dims of the respective type in spec order.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum DiscreteCrossover {
+    Uniform,
+    OnePoint,
+}
+
+/// The swarm size equals `degree_of_par`. Velocities are clamped to `max_relative_velocity` times
+/// the range of each dim, and jobs are rejected unless `max_relative_velocity > 0`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ParticleSwarmConf {
+    pub inertia: f64,
+    pub cognitive_coeff: f64,
+    pub social_coeff: f64,
+    pub max_relative_velocity: f64,
+    pub degree_of_par: usize,
+    #[serde(default)]
+    pub scheduling: Scheduling,
+}
+
+impl ParticleSwarmConf {
+    fn validate(&self) -> Result<(), String> {
+        if self.max_relative_velocity > 0.0 {
+            Ok(())
+        } else {
+            Err("Particle swarm max_relative_velocity must be greater than 0".to_string())
+        }
+    }
+}
+
+/// The initial simplex spans `initial_simplex_scale` times the range of each dim around the
+/// initial guess. Up to `degree_of_par` trial points per iteration are evaluated speculatively.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NelderMeadConf {
+    pub initial_simplex_scale: f64,
+    pub degree_of_par: usize,
+}
+
+/// Gaussian process surrogate over all completed evaluations of the job. The first
+/// `num_initial_samples` candidates are the initial guess and uniform random samples, after which
+/// batches of `degree_of_par` candidates are proposed.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BayesianOptimizationConf {
+    pub degree_of_par: usize,
+    pub num_initial_samples: usize,
+    pub acquisition: AcquisitionFunction,
+    pub constant_liar: ConstantLiar,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum AcquisitionFunction {
+    ExpectedImprovement { exploration: f64 },
+    UpperConfidenceBound { beta: f64 },
+}
+
+/// Objective function value assumed for pending candidates while a batch is proposed
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum ConstantLiar {
+    Min,
+    Mean,
+    Max,
+}
+
+/// Tree-structured Parzen Estimator. Candidates are suggested asynchronously, such that
+/// `degree_of_par` evaluations are in flight at all times. The first `num_startup_candidates`
+/// candidates are the initial guess and uniform random samples.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TpeConf {
+    pub degree_of_par: usize,
+    pub num_startup_candidates: usize,
+    /// Fraction of observations modelled as good, at most 25 of them
+    pub gamma: f64,
+    /// Number of samples from the good model per dim, among which the one with the best ratio of
+    /// good to bad density is suggested
+    pub num_ei_candidates: usize,
+    /// Weight of the uniform prior in each Parzen estimator, at least 0.001
+    pub prior_weight: f64,
+}
+
+/// NSGA-II for objective programs that return named objectives as `obj_func_vals`, all of which
+/// are minimised or maximised according to the objective direction of the job. Offspring are bred
+/// with binary tournaments on Pareto rank and crowding distance, recombined and mutated like in
+/// the genetic algorithm, and a generation of `degree_of_par` offspring is evaluated in parallel.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NsgaIIConf {
+    pub relative_std_dev: f64,
+    pub degree_of_par: usize,
+    pub crossover_prob: f64,
+    pub crossover: CrossoverConf,
+}
+
+/// Hyperband after Li et al., for objective programs that accept a budget argument. Brackets of
+/// successive halving run in turn, from the most aggressive one, which starts many random
+/// candidates at `min_budget`, to the one which evaluates few candidates at `max_budget` only.
+/// Within a bracket, the best `1 / reduction_factor` of the candidates are promoted to a budget
+/// `reduction_factor` times as large, which must be greater than 1. With `num_brackets` set to 1,
+/// this is plain successive halving. Each rung is evaluated in batches of `degree_of_par`. Jobs
+/// are rejected unless `0 < min_budget <= max_budget`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HyperbandConf {
+    pub min_budget: f64,
+    pub max_budget: f64,
+    pub reduction_factor: f64,
+    pub degree_of_par: usize,
+    #[serde(default)]
+    pub num_brackets: Option<usize>,
+}
+
+impl HyperbandConf {
+    fn validate(&self) -> Result<(), String> {
+        if self.reduction_factor <= 1.0 {
+            Err("Hyperband reduction_factor must be greater than 1".to_string())
+        } else if self.min_budget <= 0.0 || self.min_budget > self.max_budget {
+            Err("Hyperband budgets must satisfy 0 < min_budget <= max_budget".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Evaluates the full grid, keeping `degree_of_par` evaluations in flight, and terminates once
+/// exhausted. The grid of each bounded dim steps from its lower bound by `relative_resolution`
+/// times its range up to the upper bound, and jobs are rejected unless
+/// `0 < relative_resolution <= 1`. Integer steps are rounded to at least 1 and booleans take both
+/// values.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GridSearchConf {
+    pub relative_resolution: f64,
+    pub degree_of_par: usize,
+}
+
+impl GridSearchConf {
+    fn validate(&self) -> Result<(), String> {
+        if self.relative_resolution > 0.0 && self.relative_resolution <= 1.0 {
+            Ok(())
+        } else {
+            Err("Grid search relative_resolution must lie in (0, 1]".to_string())
+        }
+    }
+}
+
+/// Evaluates `num_samples` candidates sampled uniformly within the bounds, starting with the
+/// initial guess, keeping `degree_of_par` evaluations in flight, and terminates once exhausted.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RandomSearchConf {
+    pub num_samples: usize,
+    pub degree_of_par: usize,
+}
+
+/// Generalized pattern search. Each poll evaluates the neighbours of the current center one
+/// relative step up and down along every coordinate, at most `degree_of_par` at a time, and moves
+/// to the best one if it improves. Otherwise, the step is halved. Integer steps are rounded to at
+/// least 1 and booleans are flipped. Terminates once the step falls below `min_relative_step`, or
+/// once all poll points around the center have failed to improve.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PatternSearchConf {
+    pub initial_relative_step: f64,
+    pub min_relative_step: f64,
+    pub degree_of_par: usize,
+}
+
+/// Tabu search. Each step evaluates a random sample of up to `degree_of_par` neighbourhood moves
+/// in parallel and makes the best admissible one, even if it is worse than the current candidate.
+/// A move flips a boolean, changes an integer by 1 or a real number by `relative_step` times its
+/// range. Moves are tabu for `tabu_tenure` steps after a related move was made.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TabuSearchConf {
+    pub tabu_tenure: usize,
+    pub tabu_attribute: TabuAttribute,
+    pub aspiration: AspirationCriterion,
+    pub relative_step: f64,
+    pub degree_of_par: usize,
+}
+
+/// An optimizer registered with `optimizer::register` under `name`, driven by the ask/tell engine
+/// with `degree_of_par` evaluations in flight. `params` are passed to its factory as is. Jobs are
+/// rejected if no optimizer is registered under `name`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CustomConf {
+    pub name: String,
+    pub degree_of_par: usize,
+    #[serde(default)]
+    pub params: serde_json::Value,
+}
+
+impl CustomConf {
+    fn validate(&self) -> Result<(), String> {
+        if optimizer::is_registered(&self.name) {
+            Ok(())
+        } else {
+            Err(format!("No optimizer registered under name: {}", self.name))
+        }
+    }
+}
+
+/// Island model. `degree_of_par` is split evenly across the islands, overriding their own, and
+/// each island runs its algorithm independently. After every `migration_interval` evaluations of
+/// an island, the best `num_migrants` candidates of the previous island in the ring migrate to it.
+/// The running algorithm takes them in the way it takes in a warm start: population-based
+/// algorithms let them replace their worst members, model-based ones learn from them, and others
+/// ignore them. An island whose algorithm is exhausted stops. Candidates migrate by their single
+/// objective function value. Jobs are rejected unless each island gets at least one evaluation
+/// slot, i.e. `0 < islands.len() <= degree_of_par`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IslandModelConf {
+    pub degree_of_par: usize,
+    pub islands: Vec<AlgoConf>,
+    pub migration_interval: usize,
+    pub num_migrants: usize,
+}
+
+impl IslandModelConf {
+    fn validate(&self) -> Result<(), String> {
+        if self.islands.is_empty() {
+            Err("Island model requires at least one island".to_string())
+        } else if self.islands.len() > self.degree_of_par {
+            Err(format!(
+                "Island model degree_of_par {} is smaller than its number of islands {}",
+                self.degree_of_par,
+                self.islands.len()
+            ))
+        } else {
+            self.islands.iter().try_for_each(AlgoConf::validate)
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TabuAttribute {
+    /// A changed dim may not be changed again
+    Dim,
+    /// A changed dim may not return to its previous value
+    Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum AspirationCriterion {
+    /// Tabu moves are never made, and not evaluated either
+    Never,
+    /// Tabu moves are evaluated, and made if they improve on the best candidate of the search
+    ImprovesBest,
 }
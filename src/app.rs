@@ -56,6 +56,7 @@ async fn start_server(event_sender: EventSender) {
     }
 }
 
+#[allow(clippy::result_large_err)]
 async fn handle_connection(tpc_stream: TcpStream, addr: SocketAddr, event_sender: EventSender) {
     info!("Incoming TCP connection from: {}", addr);
     if let Ok(ws_stream) = tokio_tungstenite::accept_async(tpc_stream).await {
@@ -4,36 +4,57 @@ use crate::domain::DefaultProcessingJobData;
 use crate::domain::DomainState;
 use crate::domain::RequestMessage;
 use crate::domain::StatusMessage;
+use crate::domain::{
+    CandidateEvalReport, ObjectiveConf, TerminalState, TerminationConf, TerminationReason,
+};
 use crate::obj_func::ObjFuncCallDef;
 use crate::param::ParamsSpec;
 use crate::processing;
 use crate::processing_watcher::ProcessingWatcher;
-use crate::type_aliases::{AppTime, EventReceiver, EventSender, StatusSender};
+use crate::type_aliases::{AlgoConfSender, AppTime, EventReceiver, EventSender, StatusSender};
 use log::debug;
 use std::time::Duration;
-use tokio::{task::JoinHandle, time};
+use tokio::{sync::watch, task::JoinHandle, time};
 use AppEvent::*;
 use DomainStateInner::*;
 
 #[derive(Debug)]
 pub enum DomainStateInner {
     Idle(DefaultProcessingJobData),
-    Processing(Option<JoinHandle<()>>, ProcessingWatcher),
-    Terminal,
+    Processing(Option<JoinHandle<()>>, ProcessingWatcher, AlgoConfSender),
+    Terminal(ProcessingWatcher, TerminationReason),
     Error,
 }
 
 #[derive(Debug)]
 pub enum AppEvent {
     NewSubscriber(StatusSender),
-    ProcessingJob(ParamsSpec, AlgoConf, ObjFuncCallDef),
+    ProcessingJob(
+        ParamsSpec,
+        AlgoConf,
+        TerminationConf,
+        ObjectiveConf,
+        Option<WarmStartReports>,
+        ObjFuncCallDef,
+    ),
     Request(RequestMessage),
     PublishTime,
     RequestStop,
+    AlgoConfUpdate(AlgoConf),
+    ProcessingCompleted(TerminationReason),
     DelegateStatusMessage(StatusMessage),
 }
 
+/// The evaluations to warm start a processing job from, see `WarmStart`, with the reports of a
+/// file already loaded
 #[derive(Debug)]
+pub enum WarmStartReports {
+    PreviousRun,
+    File(Vec<CandidateEvalReport>),
+}
+
+#[derive(Debug)]
+#[allow(dead_code)]
 pub struct TransitionError(String);
 
 pub async fn run_app_fsm(
@@ -43,25 +64,51 @@ pub async fn run_app_fsm(
 ) {
     let mut state = DomainStateInner::Idle(default_processing_job_data.clone());
     let mut subscriber: Option<StatusSender> = None;
+    let mut previous_run_reports: Vec<CandidateEvalReport> = Vec::new();
 
     schedule_time_events(event_sender.clone());
 
     while let Some(event) = recv.recv().await {
         state = match (state, event) {
-            (Idle(_) | Terminal, ProcessingJob(spec, algo_conf, obj_func_call_def)) => {
+            (
+                Idle(_) | Terminal(..),
+                ProcessingJob(
+                    spec,
+                    algo_conf,
+                    termination_conf,
+                    objective_conf,
+                    warm_start,
+                    obj_func_call_def,
+                ),
+            ) => {
+                let imported_reports = match warm_start {
+                    Some(WarmStartReports::PreviousRun) => previous_run_reports.clone(),
+                    Some(WarmStartReports::File(reports)) => reports,
+                    None => Vec::new(),
+                };
+
                 let processing_start_instant = AppTime::now();
+                let mut processing_watcher = ProcessingWatcher::new(
+                    processing_start_instant,
+                    algo_conf.clone(),
+                    &objective_conf,
+                );
+                processing_watcher.import(&spec, &imported_reports);
+                let (algo_conf_sender, algo_conf_receiver) = watch::channel(algo_conf.clone());
+
                 let join_handle = tokio::spawn(processing::process(
                     processing_start_instant,
                     spec,
                     algo_conf,
+                    termination_conf,
+                    objective_conf,
+                    imported_reports,
                     obj_func_call_def,
+                    algo_conf_receiver,
                     event_sender.clone(),
                 ));
 
-                let new_state = Processing(
-                    Some(join_handle),
-                    ProcessingWatcher::new(processing_start_instant),
-                );
+                let new_state = Processing(Some(join_handle), processing_watcher, algo_conf_sender);
                 handle_subscription(&new_state, &mut subscriber);
                 new_state
             }
@@ -73,7 +120,7 @@ pub async fn run_app_fsm(
             (mut state, AppEvent::DelegateStatusMessage(status_msg)) => {
                 let current_time = AppTime::now();
 
-                if let Processing(_, processing_watcher) = &mut state {
+                if let Processing(_, processing_watcher, _) = &mut state {
                     processing_watcher.update(current_time);
                     processing_watcher.on_delegate_status_msg(&status_msg);
                 }
@@ -84,9 +131,10 @@ pub async fn run_app_fsm(
 
                 state
             }
-            (Processing(mut join_handle_option, _), RequestStop) => {
+            (Processing(mut join_handle_option, processing_watcher, _), RequestStop) => {
                 debug!("Stop requested");
                 join_handle_option.take().unwrap().abort();
+                previous_run_reports = processing_watcher.candidate_eval_reports();
                 // TODO: kill workers and think about awaiting result
 
                 // TODO: transition to terminal
@@ -94,21 +142,55 @@ pub async fn run_app_fsm(
                 handle_subscription(&new_state, &mut subscriber);
                 new_state
             }
-            (mut state, AppEvent::PublishTime) => {
-                match (&mut state, &subscriber) {
-                    (Processing(_, processing_watcher), Some(subscriber_)) => {
-                        processing_watcher.update(AppTime::now());
+            (Processing(_, processing_watcher, _), ProcessingCompleted(termination_reason)) => {
+                debug!("Processing completed: {:?}", termination_reason);
+                previous_run_reports = processing_watcher.candidate_eval_reports();
+                let new_state = Terminal(processing_watcher, termination_reason);
+                handle_subscription(&new_state, &mut subscriber);
+                new_state
+            }
+            (
+                Processing(join_handle_option, processing_watcher, algo_conf_sender),
+                AlgoConfUpdate(algo_conf),
+            ) => {
+                debug!("Algo conf update requested: {:?}", algo_conf);
+                if processing_watcher
+                    .algo_conf()
+                    .supports_update_to(&algo_conf)
+                {
+                    algo_conf_sender.send(algo_conf).ok();
+                } else {
+                    debug!("Rejecting algo conf update unsupported by the running algorithm");
+                    // Confirms the unchanged algo conf, as the update won't be applied
+                    if let Some(subscriber_) = &subscriber {
                         subscriber_
-                            .send(StatusMessage::Time(
-                                processing_watcher
-                                    .start_time
-                                    .elapsed()
-                                    .unwrap_or(Duration::ZERO)
-                                    .as_secs_f64(),
+                            .send(StatusMessage::EffectiveAlgoConf(
+                                processing_watcher.algo_conf().clone(),
                             ))
                             .ok();
                     }
-                    _ => (),
+                }
+                Processing(join_handle_option, processing_watcher, algo_conf_sender)
+            }
+            (Terminal(..), RequestStop) => {
+                let new_state = DomainStateInner::Idle(default_processing_job_data.clone());
+                handle_subscription(&new_state, &mut subscriber);
+                new_state
+            }
+            (mut state, AppEvent::PublishTime) => {
+                if let (Processing(_, processing_watcher, _), Some(subscriber_)) =
+                    (&mut state, &subscriber)
+                {
+                    processing_watcher.update(AppTime::now());
+                    subscriber_
+                        .send(StatusMessage::Time(
+                            processing_watcher
+                                .start_time
+                                .elapsed()
+                                .unwrap_or(Duration::ZERO)
+                                .as_secs_f64(),
+                        ))
+                        .ok();
                 }
                 state
             }
@@ -125,7 +207,7 @@ fn schedule_time_events(event_sender: EventSender) {
         let mut interval = time::interval(TIME_EVENT_INTERVAL);
         loop {
             interval.tick().await;
-            if let Err(_) = event_sender.send(AppEvent::PublishTime) {
+            if event_sender.send(AppEvent::PublishTime).is_err() {
                 break;
             }
         }
@@ -133,25 +215,27 @@ fn schedule_time_events(event_sender: EventSender) {
 }
 
 fn handle_subscription(domain_state: &DomainStateInner, subscriber: &mut Option<StatusSender>) {
-    match subscriber {
-        Some(subscriber_) => {
-            let full_state = match domain_state {
-                Idle(default_processing_job_data) => {
-                    DomainState::Idle(default_processing_job_data.clone())
-                }
-                Processing(_, processing_watcher) => {
-                    DomainState::Processing(processing_watcher.compute_processing_state())
-                }
-                Terminal => DomainState::Terminal,
-                Error => DomainState::Error,
-            };
-            if subscriber_
-                .send(StatusMessage::DomainState(full_state))
-                .is_err()
-            {
-                *subscriber = None;
+    if let Some(subscriber_) = subscriber {
+        let full_state = match domain_state {
+            Idle(default_processing_job_data) => {
+                DomainState::Idle(default_processing_job_data.clone())
+            }
+            Processing(_, processing_watcher, _) => {
+                DomainState::Processing(processing_watcher.compute_processing_state())
+            }
+            Terminal(processing_watcher, termination_reason) => {
+                DomainState::Terminal(TerminalState {
+                    processing_state: processing_watcher.compute_processing_state(),
+                    termination_reason: *termination_reason,
+                })
             }
+            Error => DomainState::Error,
+        };
+        if subscriber_
+            .send(StatusMessage::DomainState(full_state))
+            .is_err()
+        {
+            *subscriber = None;
         }
-        _ => (),
     }
 }
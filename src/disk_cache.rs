@@ -1,8 +1,9 @@
+use std::fs;
 use std::path::Path;
 
-use crate::domain::ProcessingJobData;
+use crate::domain::{CandidateEvalReport, ObjectiveConf, ProcessingJobData, TerminationConf};
 use crate::{
-    algo::{AlgoConf, ParallelHillClimbingConf},
+    algo::{AlgoConf, ParallelHillClimbingConf, Scheduling},
     domain::DefaultProcessingJobData,
 };
 use home;
@@ -13,6 +14,13 @@ pub fn retrieve_default_processing_job_data() -> DefaultProcessingJobData {
 
 pub fn store_default_processing_job_data(_data: &DefaultProcessingJobData) {}
 
+pub fn retrieve_candidate_eval_reports(file: &str) -> Result<Vec<CandidateEvalReport>, String> {
+    let reports_json_str = fs::read_to_string(file)
+        .map_err(|err| format!("Unable to read reports file {}: {}", file, err))?;
+    serde_json::from_str(&reports_json_str)
+        .map_err(|err| format!("Unable to deserialize reports of {}: {}", file, err))
+}
+
 fn gen_abs_path_as_string(rel_path_from_home: &Path) -> String {
     let mut home_dir = home::home_dir().expect("Unable to determine home directory");
     home_dir.push(rel_path_from_home);
@@ -29,6 +37,12 @@ fn gen_init_default_proc_job_data() -> DefaultProcessingJobData {
         algo_conf: AlgoConf::ParallelHillClimbing(ParallelHillClimbingConf {
             relative_std_dev: 0.01,
             degree_of_par: 10,
+            scheduling: Scheduling::Generational,
+            step_size_adaptation: None,
+            restart: None,
         }),
+        termination: TerminationConf::default(),
+        objective: ObjectiveConf::default(),
+        warm_start: None,
     })
 }
@@ -4,14 +4,21 @@ use serde_json;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum RequestMessage {
-    StartProcessing(ProcessingJobData),
+    StartProcessing(Box<ProcessingJobData>),
     StopProcessing,
+    /// Applied by the running algorithm at its next candidate generation, which is confirmed by an
+    /// `EffectiveAlgoConf` status message. Updates the running algorithm doesn't support, e.g. of
+    /// another algo, are rejected by confirming the unchanged algo conf.
+    UpdateAlgoConf(AlgoConf),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum StatusMessage {
     DomainState(DomainState),
     CandidateEvalReport(CandidateEvalReport),
+    StepSizeReport(StepSizeReport),
+    RestartReport(RestartReport),
+    EffectiveAlgoConf(AlgoConf),
     Time(f64),
 }
 
@@ -19,7 +26,7 @@ pub enum StatusMessage {
 pub enum DomainState {
     Idle(DefaultProcessingJobData),
     Processing(ProcessingState),
-    Terminal,
+    Terminal(TerminalState),
     Error,
 }
 
@@ -32,15 +39,156 @@ pub struct ProcessingJobData {
     pub args: Vec<String>,
     pub spec_file: String,
     pub algo_conf: AlgoConf,
+    #[serde(default)]
+    pub termination: TerminationConf,
+    #[serde(flatten)]
+    pub objective: ObjectiveConf,
+    #[serde(default)]
+    pub warm_start: Option<WarmStart>,
+}
+
+/// Evaluations of an earlier run to seed the processing job with. The best of them become the
+/// starting points of the algorithm, and model-based algorithms learn from all of them.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum WarmStart {
+    /// The evaluations of the latest processing job since the service started
+    PreviousRun,
+    /// A JSON file holding an array of candidate eval reports
+    File(String),
+}
+
+/// How objective function values are to be compared
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[serde(default)]
+pub struct ObjectiveConf {
+    pub direction: ObjectiveDirection,
+    pub constraint_handling: ConstraintHandling,
+    pub noise_handling: Option<NoiseHandlingConf>,
+    pub cache_policy: CachePolicy,
+}
+
+/// Re-evaluation of candidates of stochastic objective programs. Each candidate keeps a running
+/// mean, which algorithms receive in place of single samples. A candidate whose mean beats the
+/// incumbent's is re-evaluated until it has `min_samples` samples, and only then may become the
+/// incumbent. The incumbent is re-evaluated once every `reevaluation_interval` evaluations, or
+/// never if 0. Samples at different budgets are kept apart, the incumbent being re-evaluated at
+/// its own.
+/// Confidence intervals are reported as `confidence_z` standard errors around the mean.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct NoiseHandlingConf {
+    pub min_samples: usize,
+    pub reevaluation_interval: usize,
+    pub confidence_z: f64,
+}
+
+/// What to do with duplicates of evaluated candidates. Duplicates are recognised by their JSON,
+/// whose keys are sorted, along with their budget.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// Duplicates are evaluated like any other candidate
+    #[default]
+    Disabled,
+    /// Duplicates of successfully evaluated candidates are not evaluated again. Algorithms receive
+    /// the earlier result instead, which is reported as a cache hit on its first hit only. Cache
+    /// hits don't count as evaluations, whereas iterations are counted as usual, so that
+    /// `max_iterations_without_improvement` ends jobs only revisiting evaluated candidates.
+    NeverReevaluate,
+    /// Like `NeverReevaluate`, except under noise handling, where duplicates are evaluated again as
+    /// further samples
+    ReevaluateIfNoisy,
+    /// Duplicates are evaluated again, and algorithms receive the mean of all samples of the
+    /// candidate, like under noise handling with a single min sample and without re-evaluation of
+    /// the incumbent. Noise handling, if configured, takes precedence.
+    MergeSamples,
+}
+
+/// Direction of optimization, applying to all objectives of a job. Algorithms always minimise
+/// internally, maximised objective function values are negated for them.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ObjectiveDirection {
+    #[default]
+    Minimize,
+    Maximize,
+}
+
+impl ObjectiveDirection {
+    /// Maps an objective function value to the value to be minimised
+    pub fn to_minimized(self, obj_func_val: f64) -> f64 {
+        match self {
+            ObjectiveDirection::Minimize => obj_func_val,
+            ObjectiveDirection::Maximize => -obj_func_val,
+        }
+    }
+
+    pub fn is_better(self, obj_func_val: f64, other_obj_func_val: f64) -> bool {
+        self.to_minimized(obj_func_val) < self.to_minimized(other_obj_func_val)
+    }
+}
+
+/// How algorithms rank candidates of objective programs that return `constraint_vals`. A
+/// constraint is satisfied by a value at or below 0, the total violation of a candidate is the
+/// sum of its positive constraint values. Either way, only feasible candidates count as best seen.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub enum ConstraintHandling {
+    /// Feasible candidates beat infeasible ones, which are ranked by total violation. Infeasible
+    /// candidates rank as 1e12 times one plus their total violation, i.e. above any feasible
+    /// objective function value to be minimised below 1e12.
+    #[default]
+    FeasibilityRules,
+    /// Candidates rank by their objective function value plus `coefficient` times their total
+    /// violation, to be minimised
+    Penalty { coefficient: f64 },
+}
+
+/// Criteria to end processing with, any of which suffices. Without criteria, processing runs
+/// until stopped or until the algorithm is exhausted.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct TerminationConf {
+    pub max_evaluations: Option<usize>,
+    pub max_wall_time_secs: Option<f64>,
+    /// Reached once an objective function value at least as good as the target is seen, under
+    /// noise handling once the incumbent mean is
+    pub target_obj_func_val: Option<f64>,
+    /// Iterations are the batches of evaluations started together, e.g. a generation, or single
+    /// evaluations under steady-state scheduling. Not applicable to multi-objective jobs, which
+    /// have no best seen objective function value to improve.
+    pub max_iterations_without_improvement: Option<usize>,
+    pub max_consecutive_failures: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminationReason {
+    /// The algorithm has no candidates left to evaluate
+    Exhausted,
+    MaxEvaluations,
+    MaxWallTime,
+    TargetObjFuncVal,
+    MaxIterationsWithoutImprovement,
+    MaxConsecutiveFailures,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TerminalState {
+    pub processing_state: ProcessingState,
+    pub termination_reason: TerminationReason,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ProcessingState {
+    /// The algo conf in effect, including updates applied while processing
+    pub algo_conf: AlgoConf,
     pub recent_candidate_eval_reports: Vec<CandidateEvalReport>,
     pub best_seen_candidate_eval_reports: Vec<CandidateEvalReport>,
+    /// Best-seen tables of the islands of the island model, by island number
+    pub island_best_seen_candidate_eval_reports: Vec<Vec<CandidateEvalReport>>,
+    pub pareto_front_candidate_eval_reports: Vec<CandidateEvalReport>,
+    pub step_size_reports: Vec<StepSizeReport>,
+    pub restart_reports: Vec<RestartReport>,
     pub time: f64,
     pub window_length_hint: usize,
     pub best_seen_table_size_hint: usize,
+    pub pareto_front_size_hint: usize,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -49,7 +197,59 @@ pub struct CandidateEvalReport {
     pub start_unix_timestamp: f64,
     pub completion_time: f64,
     pub obj_func_val: Option<f64>,
+    #[serde(default)]
+    pub obj_func_vals: Option<Vec<NamedObjFuncVal>>,
     pub best_seen_obj_func_val_before: Option<f64>,
     pub candidate: serde_json::Value,
+    #[serde(default)]
+    pub budget: Option<f64>,
+    #[serde(default)]
+    pub constraint_vals: Option<Vec<f64>>,
+    #[serde(default = "feasible_by_default")]
+    pub feasible: bool,
+    /// Number of samples of the candidate so far, under noise handling
+    #[serde(default)]
+    pub num_samples: Option<usize>,
+    #[serde(default)]
+    pub mean_obj_func_val: Option<f64>,
+    #[serde(default)]
+    pub confidence_half_width: Option<f64>,
+    /// Evaluated by an earlier run and imported to warm start the processing job
+    #[serde(default)]
+    pub imported: bool,
+    /// Number of the island of the island model that evaluated the candidate
+    #[serde(default)]
+    pub island: Option<usize>,
+    /// The result was taken from the evaluation cache rather than from the objective program
+    #[serde(default)]
+    pub cache_hit: bool,
     pub latest_interleaving_completion_time: Option<f64>
 }
+
+fn feasible_by_default() -> bool {
+    true
+}
+
+/// Sum of the positive constraint values
+pub fn total_violation(constraint_vals: &[f64]) -> f64 {
+    constraint_vals.iter().map(|val| val.max(0.0)).sum()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NamedObjFuncVal {
+    pub name: String,
+    pub value: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StepSizeReport {
+    pub time: f64,
+    pub relative_std_dev: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RestartReport {
+    pub time: f64,
+    pub restart_num: usize,
+    pub population_size: usize,
+}
@@ -4,8 +4,10 @@ pub mod app_config;
 pub mod app_state;
 pub mod disk_cache;
 pub mod obj_func;
+pub mod optimizer;
 pub mod msg_handling;
 pub mod param;
+pub mod pareto;
 pub mod processing;
 pub mod processing_watcher;
 pub mod domain;
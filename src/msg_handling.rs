@@ -1,6 +1,7 @@
-use crate::app_state::AppEvent;
-use crate::domain::ProcessingJobData;
+use crate::app_state::{AppEvent, WarmStartReports};
+use crate::disk_cache;
 use crate::domain::RequestMessage::{self, *};
+use crate::domain::{ProcessingJobData, WarmStart};
 use crate::obj_func::ObjFuncCallDef;
 use crate::param::ParamsSpec;
 use crate::type_aliases::EventSender;
@@ -20,15 +21,29 @@ impl MsgHandler {
     pub fn handle(&self, msg: RequestMessage) {
         match msg {
             StartProcessing(processing_job_data) => {
-                self.handle_processing_job(processing_job_data)
+                self.handle_processing_job(*processing_job_data)
             }
             StopProcessing => {
                 self.event_sender.send(AppEvent::RequestStop).unwrap();
             }
+            UpdateAlgoConf(algo_conf) => {
+                if let Err(err) = algo_conf.validate() {
+                    info!("Invalid algo conf: {}", err);
+                    return;
+                }
+                self.event_sender
+                    .send(AppEvent::AlgoConfUpdate(algo_conf))
+                    .unwrap();
+            }
         }
     }
 
     fn handle_processing_job(&self, processing_job_data: ProcessingJobData) {
+        if let Err(err) = processing_job_data.algo_conf.validate() {
+            info!("Invalid algo conf: {}", err);
+            return;
+        }
+
         let spec_json_str =
             fs::read_to_string(processing_job_data.spec_file).expect("Unable to read spec file");
         let spec_json: serde_json::Value =
@@ -36,6 +51,19 @@ impl MsgHandler {
 
         let spec = ParamsSpec::from_json(spec_json).unwrap();
 
+        let warm_start = match processing_job_data.warm_start {
+            Some(WarmStart::PreviousRun) => Some(WarmStartReports::PreviousRun),
+            Some(WarmStart::File(file)) => match disk_cache::retrieve_candidate_eval_reports(&file)
+            {
+                Ok(reports) => Some(WarmStartReports::File(reports)),
+                Err(err) => {
+                    info!("Unable to warm start: {}", err);
+                    return;
+                }
+            },
+            None => None,
+        };
+
         let obj_func_call_def = ObjFuncCallDef {
             program: processing_job_data.program,
             args: processing_job_data.args,
@@ -50,6 +78,9 @@ impl MsgHandler {
             .send(AppEvent::ProcessingJob(
                 spec,
                 processing_job_data.algo_conf,
+                processing_job_data.termination,
+                processing_job_data.objective,
+                warm_start,
                 obj_func_call_def,
             ))
             .unwrap();
@@ -1,34 +1,51 @@
+use crate::domain::NamedObjFuncVal;
 use serde::{Deserialize, Serialize};
 use serde_json;
 use std::process::Stdio;
 
 use tokio::process::Command;
 
-#[derive(Debug, Deserialize)]
-struct ObjFuncChildResult {
-    obj_func_val: f64,
+/// Output of the objective program. Single-objective programs print `obj_func_val`,
+/// multi-objective programs print the named objectives, in a fixed order, as `obj_func_vals`.
+/// Either may add `constraint_vals`, each of which is satisfied at or below 0.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ObjFuncChildResult {
+    #[serde(default)]
+    pub obj_func_val: Option<f64>,
+    #[serde(default)]
+    pub obj_func_vals: Option<Vec<NamedObjFuncVal>>,
+    #[serde(default)]
+    pub constraint_vals: Option<Vec<f64>>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ObjFuncCallDef {
     pub program: String,
     pub args: Vec<String>,
 }
 
-pub async fn call<T: Serialize>(call_def: &ObjFuncCallDef, params: &T) -> Option<f64> {
+/// Calls the objective program with the candidate JSON as argument. With a budget, e.g. a number
+/// of epochs for multi-fidelity algorithms, the budget follows as an additional argument.
+pub async fn call<T: Serialize>(
+    call_def: &ObjFuncCallDef,
+    params: &T,
+    budget: Option<f64>,
+) -> Option<ObjFuncChildResult> {
     let child = Command::new(&call_def.program)
         .args(&call_def.args)
         .arg(serde_json::to_string(&params).unwrap())
+        .args(budget.map(|budget| budget.to_string()))
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
+        // Evaluations dropped by the caller, e.g. on migration in the island model, are killed
+        .kill_on_drop(true)
         .spawn()
-        .expect(format!("Failed to execute: {:?}", call_def).as_str());
+        .unwrap_or_else(|_| panic!("Failed to execute: {:?}", call_def));
     let output = child.wait_with_output().await.unwrap();
     if !output.stderr.is_empty() {
         // TODO: error handling
         None
     } else {
-        let result: ObjFuncChildResult = serde_json::from_slice(&output.stdout).unwrap();
-        Some(result.obj_func_val)
+        Some(serde_json::from_slice(&output.stdout).unwrap())
     }
 }
@@ -0,0 +1,51 @@
+use crate::param::{ParamsSpec, ParamsValue};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// An optimization algorithm driven by the ask/tell evaluation engine of the service, which calls
+/// the objective function, keeps track of the best candidate seen and reports each evaluation.
+/// Objective function values are always minimized, i.e. the engine takes care of the objective
+/// direction and of constraint handling.
+pub trait Optimizer: Send {
+    /// Proposes up to `n` candidates to evaluate next, `n` being the number of free evaluation
+    /// slots. Proposing none while no evaluations are in flight terminates the processing job.
+    fn ask(&mut self, n: usize) -> Vec<ParamsValue>;
+
+    /// Passes the result of the evaluation of a candidate proposed by `ask`, `None` if the
+    /// evaluation failed. Results are passed in order of completion.
+    fn tell(&mut self, candidate: ParamsValue, obj_func_val: Option<f64>);
+}
+
+/// Creates an optimizer for the given spec, from the `params` of the custom algo conf
+pub type OptimizerFactory =
+    Arc<dyn Fn(&ParamsSpec, &serde_json::Value) -> Box<dyn Optimizer> + Send + Sync>;
+
+fn registry() -> &'static Mutex<HashMap<String, OptimizerFactory>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<String, OptimizerFactory>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Registers an optimizer under the name referenced by `AlgoConf::Custom`, replacing any
+/// optimizer previously registered under that name. Meant to be called before `app::run`.
+pub fn register<F>(name: impl Into<String>, factory: F)
+where
+    F: Fn(&ParamsSpec, &serde_json::Value) -> Box<dyn Optimizer> + Send + Sync + 'static,
+{
+    registry()
+        .lock()
+        .unwrap()
+        .insert(name.into(), Arc::new(factory));
+}
+
+pub(crate) fn is_registered(name: &str) -> bool {
+    registry().lock().unwrap().contains_key(name)
+}
+
+pub(crate) fn create(
+    name: &str,
+    spec: &ParamsSpec,
+    params: &serde_json::Value,
+) -> Option<Box<dyn Optimizer>> {
+    let factory = registry().lock().unwrap().get(name).cloned()?;
+    Some(factory(spec, params))
+}
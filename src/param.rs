@@ -5,20 +5,21 @@ use serde_json::{
 };
 use Dim::*;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Dim {
     Boolean(DimSpec<bool>),
     RealNumber(DimSpecWithBounds<f64>),
     Integer(DimSpecWithBounds<i64>),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct DimSpec<T> {
     pub name: String,
     pub initial_value: T,
 }
 
-#[derive(Debug)]
+/// Both bounds are inclusive, i.e. `max_value_excl` is a valid value of the dim despite its name
+#[derive(Debug, Clone)]
 pub struct DimSpecWithBounds<T> {
     pub dim_spec: DimSpec<T>,
     pub min_value_incl: T,
@@ -43,61 +44,32 @@ impl<T> DimSpecWithBounds<T> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ParamsSpec {
     pub dims: Vec<Dim>,
 }
 
 impl ParamsSpec {
+    /// Parses a spec of the form `{"definition": {...}, "initial_guess": {...}}`. Each property
+    /// of the definition declares one dim:
+    /// - `[lower, upper]` for a real number
+    /// - `{"integer": [lower, upper]}` for an integer
+    /// - `"boolean"` for a boolean
+    ///
+    /// Bounds are inclusive.
     pub fn from_json(json: Value) -> Result<ParamsSpec, String> {
-        // TODO: place holder supporting only real number dims. Generalize and clean up.
         if let Some(values) = json.as_object() {
             if let Some(Object(inital_guess)) = values.get("initial_guess") {
                 if let Some(Object(definition)) = values.get("definition") {
                     let mut dims = Vec::new();
                     for (param_name, value) in definition {
-                        if let Some(bounds) = value.as_array() {
-                            if bounds.len() != 2 {
-                                return Err(
-                                    "Bounds array must have exactly two elements".to_string()
-                                );
-                            }
-
-                            if let (Some(lower_bound), Some(upper_bound)) =
-                                (bounds[0].as_f64(), bounds[1].as_f64())
-                            {
-                                if let Some(initial_guess_value) = inital_guess.get(param_name) {
-                                    if let Number(initial_guess_value) = initial_guess_value {
-                                        let initial_guess_value =
-                                            initial_guess_value.as_f64().unwrap();
-
-                                        dims.push(RealNumber(DimSpecWithBounds::new(
-                                            param_name.clone(),
-                                            initial_guess_value,
-                                            lower_bound,
-                                            upper_bound,
-                                        )))
-                                    } else {
-                                        return Err(format!(
-                                            "Initial guess property {} not a number",
-                                            param_name
-                                        )
-                                        .to_string());
-                                    }
-                                } else {
-                                    return Err(format!("Initial guess not aligned with definition. Property {} not found in initial guess.", param_name));
-                                }
-                            } else {
-                                return Err(format!(
-                                    "Bounds for param {} are not numbers",
-                                    param_name
-                                ));
-                            }
+                        if let Some(initial_guess_value) = inital_guess.get(param_name) {
+                            dims.push(parse_dim(param_name, value, initial_guess_value)?);
                         } else {
-                            return Err("Values of definition object must be arrays".to_string());
+                            return Err(format!("Initial guess not aligned with definition. Property {} not found in initial guess.", param_name));
                         }
                     }
-                    Ok(ParamsSpec {dims})
+                    Ok(ParamsSpec { dims })
                 } else {
                     Err("Missing definition property".to_string())
                 }
@@ -110,6 +82,83 @@ impl ParamsSpec {
     }
 }
 
+fn parse_dim(param_name: &str, value: &Value, initial_guess_value: &Value) -> Result<Dim, String> {
+    match value {
+        Value::Array(bounds) => {
+            let (lower_bound, upper_bound) = parse_bounds(param_name, bounds, Value::as_f64)?;
+            if let Some(initial_guess_value) = initial_guess_value.as_f64() {
+                Ok(RealNumber(DimSpecWithBounds::new(
+                    param_name.to_string(),
+                    initial_guess_value,
+                    lower_bound,
+                    upper_bound,
+                )))
+            } else {
+                Err(format!(
+                    "Initial guess property {} not a number",
+                    param_name
+                ))
+            }
+        }
+        Object(int_definition) if int_definition.contains_key("integer") => {
+            if let Some(bounds) = int_definition.get("integer").unwrap().as_array() {
+                let (lower_bound, upper_bound) = parse_bounds(param_name, bounds, Value::as_i64)?;
+                if let Some(initial_guess_value) = initial_guess_value.as_i64() {
+                    Ok(Integer(DimSpecWithBounds::new(
+                        param_name.to_string(),
+                        initial_guess_value,
+                        lower_bound,
+                        upper_bound,
+                    )))
+                } else {
+                    Err(format!(
+                        "Initial guess property {} not an integer",
+                        param_name
+                    ))
+                }
+            } else {
+                Err(format!(
+                    "Integer bounds for param {} must be an array",
+                    param_name
+                ))
+            }
+        }
+        Value::String(type_name) if type_name == "boolean" => {
+            if let Bool(initial_guess_value) = initial_guess_value {
+                Ok(Boolean(DimSpec {
+                    name: param_name.to_string(),
+                    initial_value: *initial_guess_value,
+                }))
+            } else {
+                Err(format!(
+                    "Initial guess property {} not a boolean",
+                    param_name
+                ))
+            }
+        }
+        _ => Err(format!(
+            "Definition of param {} must be a bounds array, an integer bounds object or \"boolean\"",
+            param_name
+        )),
+    }
+}
+
+fn parse_bounds<T>(
+    param_name: &str,
+    bounds: &[Value],
+    as_bound: fn(&Value) -> Option<T>,
+) -> Result<(T, T), String> {
+    if bounds.len() != 2 {
+        return Err("Bounds array must have exactly two elements".to_string());
+    }
+
+    if let (Some(lower_bound), Some(upper_bound)) = (as_bound(&bounds[0]), as_bound(&bounds[1])) {
+        Ok((lower_bound, upper_bound))
+    } else {
+        Err(format!("Bounds for param {} are not numbers", param_name))
+    }
+}
+
 pub type ParamsValue = JsonMap<String, Value>;
 impl ParamsSpec {
     pub fn extract_initial_guess(&self) -> ParamsValue {
@@ -142,3 +191,253 @@ impl ParamsSpec {
         result
     }
 }
+
+impl ParamsSpec {
+    /// Whether the value holds exactly the dims of the spec, each with a value of its type within
+    /// its bounds
+    pub fn contains(&self, value: &ParamsValue) -> bool {
+        value.len() == self.dims.len()
+            && self.dims.iter().all(|dim| match dim {
+                Boolean(dim_spec) => value
+                    .get(&dim_spec.name)
+                    .map(Value::is_boolean)
+                    .unwrap_or(false),
+                RealNumber(dim_spec_with_bounds) => value
+                    .get(&dim_spec_with_bounds.dim_spec.name)
+                    .and_then(Value::as_f64)
+                    .map(|value| {
+                        value >= dim_spec_with_bounds.min_value_incl
+                            && value <= dim_spec_with_bounds.max_value_excl
+                    })
+                    .unwrap_or(false),
+                Integer(dim_spec_with_bounds) => value
+                    .get(&dim_spec_with_bounds.dim_spec.name)
+                    .and_then(Value::as_i64)
+                    .map(|value| {
+                        value >= dim_spec_with_bounds.min_value_incl
+                            && value <= dim_spec_with_bounds.max_value_excl
+                    })
+                    .unwrap_or(false),
+            })
+    }
+
+    /// Replaces the initial guess by the given value, which the spec must contain
+    pub fn set_initial_guess(&mut self, value: &ParamsValue) {
+        for dim in &mut self.dims {
+            match dim {
+                Boolean(dim_spec) => {
+                    dim_spec.initial_value = value.get(&dim_spec.name).unwrap().as_bool().unwrap();
+                }
+                RealNumber(dim_spec_with_bounds) => {
+                    let dim_spec = &mut dim_spec_with_bounds.dim_spec;
+                    dim_spec.initial_value = value.get(&dim_spec.name).unwrap().as_f64().unwrap();
+                }
+                Integer(dim_spec_with_bounds) => {
+                    let dim_spec = &mut dim_spec_with_bounds.dim_spec;
+                    dim_spec.initial_value = value.get(&dim_spec.name).unwrap().as_i64().unwrap();
+                }
+            }
+        }
+    }
+
+    /// Maps a params value to a point in the unit cube, one coordinate per dim. Booleans map to
+    /// 0 or 1, bounded dims are scaled linearly from their bounds.
+    pub fn to_unit_cube(&self, value: &ParamsValue) -> Vec<f64> {
+        self.dims
+            .iter()
+            .map(|dim| match dim {
+                Boolean(dim_spec) => {
+                    if value.get(&dim_spec.name).unwrap().as_bool().unwrap() {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                }
+                RealNumber(dim_spec_with_bounds) => scale_to_unit(
+                    value
+                        .get(&dim_spec_with_bounds.dim_spec.name)
+                        .unwrap()
+                        .as_f64()
+                        .unwrap(),
+                    dim_spec_with_bounds.min_value_incl,
+                    dim_spec_with_bounds.max_value_excl,
+                ),
+                Integer(dim_spec_with_bounds) => scale_to_unit(
+                    value
+                        .get(&dim_spec_with_bounds.dim_spec.name)
+                        .unwrap()
+                        .as_i64()
+                        .unwrap() as f64,
+                    dim_spec_with_bounds.min_value_incl as f64,
+                    dim_spec_with_bounds.max_value_excl as f64,
+                ),
+            })
+            .collect()
+    }
+
+    /// Inverse of `to_unit_cube`. Coordinates are clamped to the unit interval, integers are
+    /// rounded to the nearest value and booleans are true from 0.5 upwards.
+    pub fn from_unit_cube(&self, point: &[f64]) -> ParamsValue {
+        let mut result = ParamsValue::new();
+
+        for (dim, coord) in self.dims.iter().zip(point) {
+            let coord = coord.clamp(0.0, 1.0);
+            match dim {
+                Boolean(dim_spec) => {
+                    result.insert(dim_spec.name.clone(), Bool(coord >= 0.5));
+                }
+                RealNumber(dim_spec_with_bounds) => {
+                    let value = dim_spec_with_bounds.min_value_incl
+                        + coord
+                            * (dim_spec_with_bounds.max_value_excl
+                                - dim_spec_with_bounds.min_value_incl);
+                    result.insert(
+                        dim_spec_with_bounds.dim_spec.name.clone(),
+                        Number(NumberValue::from_f64(value).unwrap()),
+                    );
+                }
+                Integer(dim_spec_with_bounds) => {
+                    let min_value = dim_spec_with_bounds.min_value_incl;
+                    let max_value = dim_spec_with_bounds.max_value_excl;
+                    let value = min_value as f64 + coord * (max_value - min_value) as f64;
+                    result.insert(
+                        dim_spec_with_bounds.dim_spec.name.clone(),
+                        Number(NumberValue::from(
+                            (value.round() as i64).min(max_value).max(min_value),
+                        )),
+                    );
+                }
+            }
+        }
+        result
+    }
+}
+
+fn scale_to_unit(value: f64, min_value: f64, max_value: f64) -> f64 {
+    if max_value > min_value {
+        (value - min_value) / (max_value - min_value)
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn spec() -> ParamsSpec {
+        ParamsSpec::from_json(json!({
+            "definition": {
+                "a": "boolean",
+                "b": [-10.0, 10.0],
+                "c": {"integer": [0, 4]}
+            },
+            "initial_guess": {"a": true, "b": 5.0, "c": 1}
+        }))
+        .unwrap()
+    }
+
+    fn params_value(value: Value) -> ParamsValue {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn to_unit_cube_scales_from_bounds() {
+        let point = spec().to_unit_cube(&params_value(json!({"a": true, "b": 5.0, "c": 1})));
+
+        assert_eq!(point, vec![1.0, 0.75, 0.25]);
+    }
+
+    #[test]
+    fn from_unit_cube_inverts_to_unit_cube() {
+        let spec = spec();
+        let value = params_value(json!({"a": false, "b": -2.5, "c": 3}));
+
+        assert_eq!(spec.from_unit_cube(&spec.to_unit_cube(&value)), value);
+    }
+
+    #[test]
+    fn from_unit_cube_clamps_and_rounds() {
+        let value = spec().from_unit_cube(&[0.5, 1.5, 0.6]);
+
+        assert_eq!(value, params_value(json!({"a": true, "b": 10.0, "c": 2})));
+
+        let value = spec().from_unit_cube(&[0.49, -0.5, 0.0]);
+
+        assert_eq!(value, params_value(json!({"a": false, "b": -10.0, "c": 0})));
+    }
+
+    #[test]
+    fn parse_dim_reads_each_dim_type() {
+        match parse_dim("x", &json!([-1.5, 2.5]), &json!(0.5)).unwrap() {
+            RealNumber(dim) => {
+                assert_eq!(dim.dim_spec.initial_value, 0.5);
+                assert_eq!((dim.min_value_incl, dim.max_value_excl), (-1.5, 2.5));
+            }
+            dim => panic!("Unexpected dim {:?}", dim),
+        }
+
+        match parse_dim("x", &json!({"integer": [1, 9]}), &json!(3)).unwrap() {
+            Integer(dim) => {
+                assert_eq!(dim.dim_spec.initial_value, 3);
+                assert_eq!((dim.min_value_incl, dim.max_value_excl), (1, 9));
+            }
+            dim => panic!("Unexpected dim {:?}", dim),
+        }
+
+        match parse_dim("x", &json!("boolean"), &json!(false)).unwrap() {
+            Boolean(dim) => assert!(!dim.initial_value),
+            dim => panic!("Unexpected dim {:?}", dim),
+        }
+    }
+
+    #[test]
+    fn parse_dim_rejects_mismatched_initial_guesses() {
+        assert!(parse_dim("x", &json!([0.0, 1.0]), &json!(true)).is_err());
+        assert!(parse_dim("x", &json!({"integer": [0, 4]}), &json!(1.5)).is_err());
+        assert!(parse_dim("x", &json!("boolean"), &json!(1)).is_err());
+    }
+
+    #[test]
+    fn parse_dim_rejects_malformed_definitions() {
+        assert!(parse_dim("x", &json!([0.0]), &json!(0.0)).is_err());
+        assert!(parse_dim("x", &json!([0.0, "1"]), &json!(0.0)).is_err());
+        assert!(parse_dim("x", &json!({"integer": 4}), &json!(0)).is_err());
+        assert!(parse_dim("x", &json!("string"), &json!("a")).is_err());
+    }
+
+    #[test]
+    fn bounds_are_inclusive() {
+        let spec = spec();
+
+        assert!(spec.contains(&params_value(json!({"a": true, "b": 10.0, "c": 4}))));
+        assert!(spec.contains(&params_value(json!({"a": true, "b": -10.0, "c": 0}))));
+        assert!(!spec.contains(&params_value(json!({"a": true, "b": 10.5, "c": 4}))));
+        assert!(!spec.contains(&params_value(json!({"a": true, "b": 10.0, "c": 5}))));
+        assert!(!spec.contains(&params_value(json!({"a": true, "b": 0.0, "c": -1}))));
+
+        assert_eq!(
+            spec.from_unit_cube(&[1.0, 1.0, 1.0]),
+            params_value(json!({"a": true, "b": 10.0, "c": 4}))
+        );
+        assert_eq!(
+            spec.to_unit_cube(&params_value(json!({"a": true, "b": 10.0, "c": 4}))),
+            vec![1.0, 1.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn dims_without_range_map_to_the_origin() {
+        let spec = ParamsSpec::from_json(json!({
+            "definition": {"x": [1.0, 1.0]},
+            "initial_guess": {"x": 1.0}
+        }))
+        .unwrap();
+
+        assert_eq!(
+            spec.to_unit_cube(&params_value(json!({"x": 1.0}))),
+            vec![0.0]
+        );
+    }
+}
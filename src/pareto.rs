@@ -0,0 +1,88 @@
+//! Pareto dominance for minimised objective vectors.
+
+/// Returns whether `a` is no worse than `b` in all objectives and better in at least one.
+/// Vectors of different lengths do not dominate each other.
+pub fn dominates(a: &[f64], b: &[f64]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(a, b)| a <= b)
+        && a.iter().zip(b).any(|(a, b)| a < b)
+}
+
+/// Crowding distances of the given objective vectors, after Deb et al., "A Fast and Elitist
+/// Multiobjective Genetic Algorithm: NSGA-II". Boundary points get an infinite distance.
+pub fn crowding_distances(points: &[&[f64]]) -> Vec<f64> {
+    let mut distances = vec![0.0; points.len()];
+    let num_objectives = points.first().map(|point| point.len()).unwrap_or(0);
+
+    for objective_index in 0..num_objectives {
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        order.sort_by(|&a, &b| {
+            points[a][objective_index]
+                .partial_cmp(&points[b][objective_index])
+                .unwrap()
+        });
+
+        let first = points[order[0]][objective_index];
+        let last = points[order[order.len() - 1]][objective_index];
+        distances[order[0]] = f64::INFINITY;
+        distances[order[order.len() - 1]] = f64::INFINITY;
+
+        if last > first {
+            for window in order.windows(3) {
+                distances[window[1]] += (points[window[2]][objective_index]
+                    - points[window[0]][objective_index])
+                    / (last - first);
+            }
+        }
+    }
+
+    distances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dominates_requires_no_worse_and_one_better() {
+        assert!(dominates(&[1.0, 2.0], &[1.0, 3.0]));
+        assert!(dominates(&[0.0, 0.0], &[1.0, 1.0]));
+        assert!(!dominates(&[1.0, 2.0], &[1.0, 2.0]));
+        assert!(!dominates(&[1.0, 3.0], &[2.0, 2.0]));
+        assert!(!dominates(&[1.0, 3.0], &[1.0, 2.0]));
+    }
+
+    #[test]
+    fn vectors_of_different_lengths_do_not_dominate() {
+        assert!(!dominates(&[0.0], &[1.0, 1.0]));
+        assert!(!dominates(&[0.0, 0.0], &[1.0]));
+    }
+
+    #[test]
+    fn crowding_distances_sum_normalized_neighbour_gaps() {
+        let points: [&[f64]; 4] = [&[0.0, 4.0], &[1.0, 2.0], &[3.0, 1.0], &[4.0, 0.0]];
+
+        let distances = crowding_distances(&points);
+
+        assert_eq!(distances[0], f64::INFINITY);
+        assert!((distances[1] - 1.5).abs() < 1e-12);
+        assert!((distances[2] - 1.25).abs() < 1e-12);
+        assert_eq!(distances[3], f64::INFINITY);
+    }
+
+    #[test]
+    fn crowding_distances_ignore_objectives_without_range() {
+        let points: [&[f64]; 3] = [&[1.0, 0.0], &[1.0, 1.0], &[1.0, 3.0]];
+
+        let distances = crowding_distances(&points);
+
+        assert_eq!(distances[0], f64::INFINITY);
+        assert!((distances[1] - 1.0).abs() < 1e-12);
+        assert_eq!(distances[2], f64::INFINITY);
+    }
+
+    #[test]
+    fn crowding_distances_of_no_points_are_empty() {
+        assert!(crowding_distances(&[]).is_empty());
+    }
+}
@@ -1,14 +1,43 @@
+mod ask_tell;
+mod bayesian_optimization;
+mod cache;
+mod cma_es;
+mod differential_evolution;
+mod genetic;
+mod grid_search;
+mod hyperband;
+mod island_model;
+mod nelder_mead;
+mod noise;
+mod nsga_ii;
+mod parallel_hill_climbing;
+mod particle_swarm;
+mod pattern_search;
+mod random_search;
+mod simulated_annealing;
+mod stats;
+mod tabu_search;
+mod tpe;
+
 use crate::app_state::AppEvent;
-use crate::domain::{CandidateEvalReport, StatusMessage};
-use crate::obj_func::{self, ObjFuncCallDef};
+use crate::domain::{
+    total_violation, CachePolicy, CandidateEvalReport, ConstraintHandling, NoiseHandlingConf,
+    ObjectiveConf, ObjectiveDirection, RestartReport, StatusMessage, StepSizeReport,
+    TerminationConf, TerminationReason,
+};
+use crate::obj_func::{self, ObjFuncCallDef, ObjFuncChildResult};
+use crate::optimizer;
 use crate::param::Dim;
-use crate::type_aliases::EventSender;
+use crate::type_aliases::{AlgoConfReceiver, EventSender};
+use cache::EvaluationCache;
 use futures::future;
-use log::debug;
-use rand::SeedableRng;
+use island_model::Island;
+use log::{debug, info};
+use noise::Samples;
 use rand::{
     distributions::{Bernoulli, Distribution},
     rngs::StdRng,
+    Rng,
 };
 use rand_distr::Normal;
 use serde_json::Number as NumberValue;
@@ -16,31 +45,118 @@ use serde_json::Value::{Bool, Number, Object};
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::{Duration, SystemTime};
+use tokio::sync::Notify;
+use tokio::{task, time};
 
-use crate::algo::{
-    AlgoConf::{self, *},
-    ParallelHillClimbingConf,
-};
+use crate::algo::AlgoConf::{self, *};
 use crate::param::{ParamsSpec, ParamsValue};
 use crate::type_aliases::AppTime;
 
+/// Confidence intervals of merged samples of duplicates are reported at 95%
+const MERGED_SAMPLES_CONFIDENCE_Z: f64 = 1.96;
+
+/// Under feasibility rules, infeasible candidates rank as this value times one plus their total
+/// violation, hence above any feasible objective function value below it
+const INFEASIBLE_FITNESS_BASE: f64 = 1e12;
+
+#[allow(clippy::too_many_arguments)]
 pub async fn process(
     processing_start_instant: AppTime,
     spec: ParamsSpec,
     algo_conf: AlgoConf,
+    termination_conf: TerminationConf,
+    objective_conf: ObjectiveConf,
+    imported_reports: Vec<CandidateEvalReport>,
     obj_func_call_def: ObjFuncCallDef,
+    algo_conf_receiver: AlgoConfReceiver,
     event_sender: EventSender,
 ) {
+    let mut ctx = ProcessingContext::new(
+        processing_start_instant,
+        obj_func_call_def,
+        termination_conf,
+        objective_conf,
+        event_sender,
+        &spec,
+        imported_reports,
+    );
+    ctx.algo_conf_updates = Some(Mutex::new(algo_conf_receiver));
+
+    let termination_reason = tokio::select! {
+        _ = run(&ctx, spec, algo_conf) => TerminationReason::Exhausted,
+        termination_reason = ctx.termination.reached(ctx.elapsed_secs()) => termination_reason,
+    };
+
+    debug!("Processing completed: {:?}", termination_reason);
+    ctx.event_sender
+        .send(AppEvent::ProcessingCompleted(termination_reason))
+        .ok();
+}
+
+async fn run(ctx: &ProcessingContext, mut spec: ParamsSpec, algo_conf: AlgoConf) {
+    if let Some(best_candidate) = ctx.imported_candidate(0) {
+        debug!("Warm starting from: {:?}", best_candidate);
+        spec.set_initial_guess(best_candidate);
+    }
+
     match algo_conf {
         ParallelHillClimbing(parallel_hill_climbing_conf) => {
-            parallel_hill_climbing(
-                processing_start_instant,
-                spec,
-                parallel_hill_climbing_conf,
-                obj_func_call_def,
-                event_sender,
-            )
-            .await;
+            parallel_hill_climbing::run(ctx, spec, parallel_hill_climbing_conf).await;
+        }
+        SimulatedAnnealing(simulated_annealing_conf) => {
+            simulated_annealing::run(ctx, spec, simulated_annealing_conf).await;
+        }
+        CmaEs(cma_es_conf) => {
+            cma_es::run(ctx, spec, cma_es_conf).await;
+        }
+        DifferentialEvolution(differential_evolution_conf) => {
+            differential_evolution::run(ctx, spec, differential_evolution_conf).await;
+        }
+        Genetic(genetic_conf) => {
+            genetic::run(ctx, spec, genetic_conf).await;
+        }
+        ParticleSwarm(particle_swarm_conf) => {
+            particle_swarm::run(ctx, spec, particle_swarm_conf).await;
+        }
+        NelderMead(nelder_mead_conf) => {
+            nelder_mead::run(ctx, spec, nelder_mead_conf).await;
+        }
+        BayesianOptimization(bayesian_optimization_conf) => {
+            bayesian_optimization::run(ctx, spec, bayesian_optimization_conf).await;
+        }
+        Tpe(tpe_conf) => {
+            tpe::run(ctx, spec, tpe_conf).await;
+        }
+        NsgaII(nsga_ii_conf) => {
+            nsga_ii::run(ctx, spec, nsga_ii_conf).await;
+        }
+        Hyperband(hyperband_conf) => {
+            hyperband::run(ctx, spec, hyperband_conf).await;
+        }
+        GridSearch(grid_search_conf) => {
+            grid_search::run(ctx, spec, grid_search_conf).await;
+        }
+        RandomSearch(random_search_conf) => {
+            let degree_of_par = random_search_conf.degree_of_par;
+            let optimizer = random_search::RandomSearch::new(spec, &random_search_conf);
+            ask_tell::run(ctx, Box::new(optimizer), degree_of_par).await;
+        }
+        PatternSearch(pattern_search_conf) => {
+            pattern_search::run(ctx, spec, pattern_search_conf).await;
+        }
+        TabuSearch(tabu_search_conf) => {
+            tabu_search::run(ctx, spec, tabu_search_conf).await;
+        }
+        Custom(custom_conf) => {
+            match optimizer::create(&custom_conf.name, &spec, &custom_conf.params) {
+                Some(optimizer) => {
+                    ask_tell::run(ctx, optimizer, custom_conf.degree_of_par).await;
+                }
+                None => info!("No optimizer registered under name: {}", custom_conf.name),
+            }
+        }
+        IslandModel(island_model_conf) => {
+            island_model::run(ctx, spec, island_model_conf).await;
         }
     }
 }
@@ -54,89 +170,724 @@ struct Seen {
 
 type SeenContext = Arc<Mutex<Option<Seen>>>;
 
-async fn parallel_hill_climbing(
+/// A candidate with its objective function value to be minimised, as passed from earlier runs
+/// or islands to warm start algorithms
+#[derive(Debug, Clone)]
+struct Evaluation {
+    candidate: ParamsValue,
+    obj_func_val: Option<f64>,
+}
+
+#[derive(Debug, Default)]
+struct EvaluationCounts {
+    num_started: usize,
+    num_evaluations: usize,
+    num_iterations_without_improvement: usize,
+    improved_since_iteration: bool,
+    num_consecutive_failures: usize,
+}
+
+/// Termination criteria of a processing job, along with the evaluation counts they are checked
+/// against. The first criterion met is recorded and wakes up `reached`.
+struct Termination {
+    conf: TerminationConf,
+    direction: ObjectiveDirection,
+    counts: Mutex<EvaluationCounts>,
+    reason: Mutex<Option<TerminationReason>>,
+    notify: Notify,
+}
+
+impl Termination {
+    fn new(conf: TerminationConf, direction: ObjectiveDirection) -> Termination {
+        Termination {
+            conf,
+            direction,
+            counts: Mutex::new(EvaluationCounts::default()),
+            reason: Mutex::new(None),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Starts another evaluation, unless that would exceed the max evaluations
+    fn try_start(&self) -> Option<EvaluationStart<'_>> {
+        let mut counts = self.counts.lock().unwrap();
+        if self
+            .conf
+            .max_evaluations
+            .map(|max_evaluations| counts.num_started >= max_evaluations)
+            .unwrap_or(false)
+        {
+            None
+        } else {
+            counts.num_started += 1;
+            Some(EvaluationStart {
+                termination: self,
+                completed: false,
+            })
+        }
+    }
+
+    fn on_evaluation(&self, obj_func_val: Option<f64>, failed: bool, improved: bool) {
+        let mut counts = self.counts.lock().unwrap();
+        counts.num_evaluations += 1;
+        counts.improved_since_iteration |= improved;
+        if failed {
+            counts.num_consecutive_failures += 1;
+        } else {
+            counts.num_consecutive_failures = 0;
+        }
+
+        let reached =
+            |max: Option<usize>, count: usize| max.map(|max| count >= max).unwrap_or(false);
+
+        let reason = if self
+            .conf
+            .target_obj_func_val
+            .zip(obj_func_val)
+            .map(|(target, obj_func_val)| !self.direction.is_better(target, obj_func_val))
+            .unwrap_or(false)
+        {
+            Some(TerminationReason::TargetObjFuncVal)
+        } else if reached(self.conf.max_evaluations, counts.num_evaluations) {
+            Some(TerminationReason::MaxEvaluations)
+        } else if reached(
+            self.conf.max_consecutive_failures,
+            counts.num_consecutive_failures,
+        ) {
+            Some(TerminationReason::MaxConsecutiveFailures)
+        } else {
+            None
+        };
+
+        if let Some(reason) = reason {
+            self.terminate(reason);
+        }
+    }
+
+    /// Counts a completed iteration, which has improved if any evaluation has improved since the
+    /// previous one completed, possibly an evaluation of another iteration in flight
+    fn on_iteration(&self) {
+        let mut counts = self.counts.lock().unwrap();
+        if counts.improved_since_iteration {
+            counts.num_iterations_without_improvement = 0;
+            counts.improved_since_iteration = false;
+        } else {
+            counts.num_iterations_without_improvement += 1;
+        }
+
+        if self
+            .conf
+            .max_iterations_without_improvement
+            .map(|max| counts.num_iterations_without_improvement >= max)
+            .unwrap_or(false)
+        {
+            self.terminate(TerminationReason::MaxIterationsWithoutImprovement);
+        }
+    }
+
+    fn terminate(&self, reason: TerminationReason) {
+        let mut reason_option = self.reason.lock().unwrap();
+        if reason_option.is_none() {
+            *reason_option = Some(reason);
+            self.notify.notify_one();
+        }
+    }
+
+    async fn reached(&self, elapsed_secs: f64) -> TerminationReason {
+        // No evaluation ever starts, hence `on_evaluation` would never be called
+        if self.conf.max_evaluations == Some(0) {
+            return TerminationReason::MaxEvaluations;
+        }
+
+        let max_wall_time = async {
+            match self.conf.max_wall_time_secs {
+                Some(max_wall_time_secs) => {
+                    time::sleep(Duration::from_secs_f64(
+                        (max_wall_time_secs - elapsed_secs).max(0.0),
+                    ))
+                    .await
+                }
+                None => future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            _ = max_wall_time => TerminationReason::MaxWallTime,
+            _ = self.notify.notified() => self.reason.lock().unwrap().unwrap(),
+        }
+    }
+}
+
+/// A started evaluation, which is given back if dropped before completion, i.e. if the algorithm
+/// is dropped while the evaluation is in flight
+struct EvaluationStart<'a> {
+    termination: &'a Termination,
+    completed: bool,
+}
+
+impl Drop for EvaluationStart<'_> {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.termination.counts.lock().unwrap().num_started -= 1;
+        }
+    }
+}
+
+/// Evaluation and reporting plumbing shared by all algorithms of a processing job.
+///
+/// Objective function values are passed to algorithms as values to be minimised, whereas
+/// reports carry the values as returned by the objective program. Constraint violations are
+/// accounted for in the values passed to algorithms, according to the constraint handling.
+struct ProcessingContext {
     processing_start_instant: AppTime,
-    spec: ParamsSpec,
-    algo_conf: ParallelHillClimbingConf,
     obj_func_call_def: ObjFuncCallDef,
+    direction: ObjectiveDirection,
+    constraint_handling: ConstraintHandling,
     event_sender: EventSender,
-) {
-    let initial_guess = Object(spec.extract_initial_guess());
-    let seen: SeenContext = Arc::new(Mutex::new(None));
-
-    debug!("Starting with initial guess: {:?}", &initial_guess);
-    let mut rng = StdRng::seed_from_u64(0);
-
-    for iter_num in 0.. {
-        let candidates: Vec<serde_json::Value> = (0..algo_conf.degree_of_par)
-            .map(|candidate_number| {
-                if iter_num == 0 && candidate_number == 0 {
-                    initial_guess.clone()
-                } else {
-                    let seen_option = seen.lock().unwrap();
-                    let from_candidate = seen_option
+    seen: SeenContext,
+    /// Present under noise handling, or when merging samples of duplicates
+    samples: Option<Arc<Mutex<Samples>>>,
+    /// Present when duplicates are not re-evaluated
+    cache: Option<Arc<Mutex<EvaluationCache>>>,
+    termination: Arc<Termination>,
+    /// Sorted from best to worst, failed evaluations last
+    imported: Vec<Evaluation>,
+    /// Present on the islands of the island model
+    island: Option<Arc<Island>>,
+    /// Absent on the islands of the island model, which do not support updates
+    algo_conf_updates: Option<Mutex<AlgoConfReceiver>>,
+}
+
+impl ProcessingContext {
+    fn new(
+        processing_start_instant: AppTime,
+        obj_func_call_def: ObjFuncCallDef,
+        termination_conf: TerminationConf,
+        objective_conf: ObjectiveConf,
+        event_sender: EventSender,
+        spec: &ParamsSpec,
+        imported_reports: Vec<CandidateEvalReport>,
+    ) -> ProcessingContext {
+        let mut ctx = ProcessingContext {
+            processing_start_instant,
+            obj_func_call_def,
+            direction: objective_conf.direction,
+            constraint_handling: objective_conf.constraint_handling,
+            event_sender,
+            seen: Arc::new(Mutex::new(None)),
+            samples: objective_conf
+                .noise_handling
+                .or(match objective_conf.cache_policy {
+                    CachePolicy::MergeSamples => Some(NoiseHandlingConf {
+                        min_samples: 1,
+                        reevaluation_interval: 0,
+                        confidence_z: MERGED_SAMPLES_CONFIDENCE_Z,
+                    }),
+                    _ => None,
+                })
+                .map(|conf| Arc::new(Mutex::new(Samples::new(conf, objective_conf.direction)))),
+            cache: match (objective_conf.cache_policy, objective_conf.noise_handling) {
+                (CachePolicy::NeverReevaluate, _) | (CachePolicy::ReevaluateIfNoisy, None) => {
+                    Some(Arc::new(Mutex::new(EvaluationCache::default())))
+                }
+                _ => None,
+            },
+            termination: Arc::new(Termination::new(termination_conf, objective_conf.direction)),
+            imported: Vec::new(),
+            island: None,
+            algo_conf_updates: None,
+        };
+        ctx.imported = ctx.import(spec, imported_reports);
+        ctx
+    }
+
+    /// A context sharing everything, whose evaluations are tagged with and recorded on the island.
+    /// The job-wide best seen candidate is only shared for reporting.
+    fn for_island(&self, island: Arc<Island>) -> ProcessingContext {
+        ProcessingContext {
+            processing_start_instant: self.processing_start_instant,
+            obj_func_call_def: self.obj_func_call_def.clone(),
+            direction: self.direction,
+            constraint_handling: self.constraint_handling,
+            event_sender: self.event_sender.clone(),
+            seen: self.seen.clone(),
+            samples: self.samples.clone(),
+            cache: self.cache.clone(),
+            termination: self.termination.clone(),
+            imported: self.imported.clone(),
+            island: Some(island),
+            algo_conf_updates: None,
+        }
+    }
+
+    /// Converts the reports of an earlier run whose candidates the spec contains. Their feasible
+    /// values count towards the best seen value, except under noise handling, where the
+    /// incumbent is determined from samples of this run only.
+    fn import(&self, spec: &ParamsSpec, reports: Vec<CandidateEvalReport>) -> Vec<Evaluation> {
+        let results: Vec<(ParamsValue, ObjFuncChildResult)> = reports
+            .into_iter()
+            .filter_map(|report| match report.candidate {
+                Object(candidate) if spec.contains(&candidate) => Some((
+                    candidate,
+                    ObjFuncChildResult {
+                        obj_func_val: report.obj_func_val,
+                        obj_func_vals: report.obj_func_vals,
+                        constraint_vals: report.constraint_vals,
+                    },
+                )),
+                _ => None,
+            })
+            .collect();
+
+        for (candidate, result) in &results {
+            if let Some(obj_func_val) = result.obj_func_val.filter(|_| is_feasible(result)) {
+                let mut seen_option = self.seen.lock().unwrap();
+                if self.samples.is_none()
+                    && seen_option
                         .as_ref()
-                        .map(|seen_| &seen_.best_candidate)
-                        .unwrap_or(&initial_guess)
-                        .as_object()
-                        .unwrap();
-
-                    Object(create_candidate(
-                        from_candidate,
-                        &spec,
-                        &algo_conf,
-                        &mut rng,
-                    ))
+                        .map(|seen| {
+                            self.direction
+                                .is_better(obj_func_val, seen.best_obj_func_val)
+                        })
+                        .unwrap_or(true)
+                {
+                    *seen_option = Some(Seen {
+                        best_candidate: Object(candidate.clone()),
+                        best_obj_func_val: obj_func_val,
+                        latest_completion_time: 0.0,
+                    });
                 }
+            }
+        }
+
+        let mut imported: Vec<Evaluation> = results
+            .into_iter()
+            .map(|(candidate, result)| Evaluation {
+                candidate,
+                obj_func_val: self.fitness(Some(result)),
             })
             .collect();
+        sort_evaluations(&mut imported);
+
+        debug!("Imported {} evaluations", imported.len());
+        imported
+    }
+
+    /// The imported candidate of the given rank, failed evaluations excluded. Meant to seed
+    /// starting points, populations included.
+    fn imported_candidate(&self, rank: usize) -> Option<&ParamsValue> {
+        self.imported
+            .get(rank)
+            .filter(|imported| imported.obj_func_val.is_some())
+            .map(|imported| &imported.candidate)
+    }
+
+    /// The migrants received by the island since the previous call, best first. Algorithms that
+    /// take in imported evaluations take in migrants the same way while running.
+    fn migrants(&self) -> Vec<Evaluation> {
+        self.island
+            .as_ref()
+            .map(|island| island.take_migrants())
+            .unwrap_or_default()
+    }
+
+    /// Evaluates the candidates in parallel, reporting each one as it completes. The
+    /// objective function values are returned in the order of the candidates.
+    async fn evaluate(&self, candidates: Vec<serde_json::Value>) -> Vec<Option<f64>> {
+        let results = self.evaluate_results(candidates, None).await;
+        self.termination.on_iteration();
+        results
+            .into_iter()
+            .map(|result| self.fitness(result))
+            .collect()
+    }
+
+    /// Like `evaluate`, but returns the vectors of objective function values of a
+    /// multi-objective program. Under feasibility rules, infeasible candidates are returned as
+    /// failed evaluations. Under the penalty mode, each objective is penalised alike. Iterations
+    /// are not counted, since there is no best seen value to improve.
+    async fn evaluate_multi(&self, candidates: Vec<serde_json::Value>) -> Vec<Option<Vec<f64>>> {
+        self.evaluate_results(candidates, None)
+            .await
+            .into_iter()
+            .map(|result| {
+                let result = result?;
+                let violation = total_violation(result.constraint_vals.as_deref().unwrap_or(&[]));
+                let penalty = match self.constraint_handling {
+                    ConstraintHandling::FeasibilityRules if violation > 0.0 => return None,
+                    ConstraintHandling::FeasibilityRules => 0.0,
+                    ConstraintHandling::Penalty { coefficient } => coefficient * violation,
+                };
+                result.obj_func_vals.map(|obj_func_vals| {
+                    obj_func_vals
+                        .iter()
+                        .map(|val| self.direction.to_minimized(val.value) + penalty)
+                        .collect()
+                })
+            })
+            .collect()
+    }
+
+    /// Like `evaluate`, passing the budget to the objective program, which evaluates the
+    /// candidates at the corresponding fidelity.
+    async fn evaluate_with_budget(
+        &self,
+        candidates: Vec<serde_json::Value>,
+        budget: f64,
+    ) -> Vec<Option<f64>> {
+        let results = self.evaluate_results(candidates, Some(budget)).await;
+        self.termination.on_iteration();
+        results
+            .into_iter()
+            .map(|result| self.fitness(result))
+            .collect()
+    }
+
+    async fn evaluate_results(
+        &self,
+        candidates: Vec<serde_json::Value>,
+        budget: Option<f64>,
+    ) -> Vec<Option<ObjFuncChildResult>> {
+        let iteration_start_time = self.elapsed_secs();
+
+        let eval_candidate_futures = candidates.into_iter().map(|candidate| {
+            self.evaluate_candidate_and_report(candidate, budget, iteration_start_time)
+        });
+
+        future::join_all(eval_candidate_futures).await
+    }
+
+    /// Evaluates a single candidate and reports it, independently of any other evaluations, which
+    /// counts as an iteration of its own.
+    async fn evaluate_one(&self, candidate: serde_json::Value) -> Option<f64> {
+        let result = self
+            .evaluate_candidate_and_report(candidate, None, self.elapsed_secs())
+            .await;
+        self.termination.on_iteration();
+        self.fitness(result)
+    }
+
+    /// Like `evaluate_one`, passing the tag through. Used to keep a pool of evaluations in flight
+    /// under steady-state scheduling.
+    async fn evaluate_tagged<T>(&self, tag: T, candidate: serde_json::Value) -> (T, Option<f64>) {
+        (tag, self.evaluate_one(candidate).await)
+    }
+
+    /// The algo conf requested for the running algorithm, if updated since the previous call
+    fn algo_conf_update(&self) -> Option<AlgoConf> {
+        let mut algo_conf_receiver = self.algo_conf_updates.as_ref()?.lock().unwrap();
+        if algo_conf_receiver.has_changed().unwrap_or(false) {
+            Some(algo_conf_receiver.borrow_and_update().clone())
+        } else {
+            None
+        }
+    }
+
+    fn report_effective_algo_conf(&self, algo_conf: AlgoConf) {
+        self.event_sender
+            .send(AppEvent::DelegateStatusMessage(
+                StatusMessage::EffectiveAlgoConf(algo_conf),
+            ))
+            .ok();
+    }
+
+    fn report_step_size(&self, relative_std_dev: f64) {
+        self.event_sender
+            .send(AppEvent::DelegateStatusMessage(
+                StatusMessage::StepSizeReport(StepSizeReport {
+                    time: self.elapsed_secs(),
+                    relative_std_dev,
+                }),
+            ))
+            .ok();
+    }
+
+    fn report_restart(&self, restart_num: usize, population_size: usize) {
+        self.event_sender
+            .send(AppEvent::DelegateStatusMessage(
+                StatusMessage::RestartReport(RestartReport {
+                    time: self.elapsed_secs(),
+                    restart_num,
+                    population_size,
+                }),
+            ))
+            .ok();
+    }
 
-        let iteration_start_time = processing_start_instant
+    /// The best seen candidate, on islands of the island model the island's own best
+    fn best_candidate(&self) -> Option<serde_json::Value> {
+        if let Some(island) = &self.island {
+            return island
+                .best_evaluation()
+                .map(|evaluation| Object(evaluation.candidate));
+        }
+
+        self.seen
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|seen| seen.best_candidate.clone())
+    }
+
+    /// The best seen objective function value, as a value to be minimised, on islands of the
+    /// island model the island's own best
+    fn best_obj_func_val(&self) -> Option<f64> {
+        if let Some(island) = &self.island {
+            return island
+                .best_evaluation()
+                .and_then(|evaluation| evaluation.obj_func_val);
+        }
+
+        self.seen
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|seen| self.direction.to_minimized(seen.best_obj_func_val))
+    }
+
+    /// The value to be minimised by algorithms, see `ConstraintHandling`
+    fn fitness(&self, result: Option<ObjFuncChildResult>) -> Option<f64> {
+        let result = result?;
+        let obj_func_val = self.direction.to_minimized(result.obj_func_val?);
+        let violation = total_violation(result.constraint_vals.as_deref().unwrap_or(&[]));
+
+        match self.constraint_handling {
+            ConstraintHandling::FeasibilityRules if violation > 0.0 => {
+                Some(INFEASIBLE_FITNESS_BASE * (1.0 + violation))
+            }
+            ConstraintHandling::FeasibilityRules => Some(obj_func_val),
+            ConstraintHandling::Penalty { coefficient } => {
+                Some(obj_func_val + coefficient * violation)
+            }
+        }
+    }
+
+    fn elapsed_secs(&self) -> f64 {
+        self.processing_start_instant
             .elapsed()
             .unwrap_or(Duration::ZERO)
-            .as_secs_f64();
+            .as_secs_f64()
+    }
 
-        let eval_candidate_futures = candidates.into_iter().map(|candidate| {
-            evaluate_candidate_and_report(
-                &obj_func_call_def,
+    async fn evaluate_candidate_and_report(
+        &self,
+        new_candidate: serde_json::Value,
+        budget: Option<f64>,
+        iteration_start_time: f64,
+    ) -> Option<ObjFuncChildResult> {
+        let mut evaluation_start = match self.termination.try_start() {
+            Some(evaluation_start) => evaluation_start,
+            // Processing ends once the evaluations in flight have completed
+            None => return future::pending().await,
+        };
+
+        let cache_hit = self
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.lock().unwrap().hit(&new_candidate, budget));
+
+        // Cache hits are no evaluations, hence their start is given back and only the first hit
+        // of a result is reported
+        let (mut result, cache_hit) = match cache_hit {
+            Some((cached_result, first_hit)) => {
+                // Cache hits complete right away and must not starve the other tasks
+                task::yield_now().await;
+                if first_hit {
+                    self.report(
+                        new_candidate.clone(),
+                        budget,
+                        iteration_start_time,
+                        Some(&cached_result),
+                        true,
+                    );
+                }
+                (Some(cached_result), true)
+            }
+            None => {
+                let result = self
+                    .evaluate_candidate(
+                        new_candidate.clone(),
+                        budget,
+                        iteration_start_time,
+                        &mut evaluation_start,
+                    )
+                    .await;
+                (result, false)
+            }
+        };
+
+        if let Some(samples) = &self.samples {
+            if let (Some(result), Some(stats)) = (
+                result.as_mut(),
+                samples.lock().unwrap().get(&new_candidate, budget),
+            ) {
+                result.obj_func_val = Some(stats.mean);
+            }
+        }
+
+        if let (Some(island), Object(candidate), false) = (&self.island, new_candidate, cache_hit) {
+            island.record(Evaluation {
                 candidate,
-                seen.clone(),
-                &processing_start_instant,
-                iteration_start_time,
-                event_sender.clone(),
-            )
-        });
+                obj_func_val: self.fitness(result.clone()),
+            });
+        }
 
-        future::join_all(eval_candidate_futures).await;
+        result
+    }
+
+    /// Evaluates the candidate, and re-evaluates promising challengers and the incumbent under
+    /// noise handling. Re-evaluations count as evaluations, hence they are only started while the
+    /// max evaluations allow for them.
+    async fn evaluate_candidate(
+        &self,
+        new_candidate: serde_json::Value,
+        budget: Option<f64>,
+        iteration_start_time: f64,
+        evaluation_start: &mut EvaluationStart<'_>,
+    ) -> Option<ObjFuncChildResult> {
+        let mut result = self
+            .sample_and_report(new_candidate.clone(), budget, iteration_start_time)
+            .await;
+        evaluation_start.completed = true;
+
+        if let Some(samples) = &self.samples {
+            loop {
+                let num_samples_before = {
+                    let samples = samples.lock().unwrap();
+                    if !samples.is_promising_challenger(&new_candidate, budget) {
+                        break;
+                    }
+                    samples.get(&new_candidate, budget).unwrap().num_samples
+                };
+
+                let mut reevaluation_start = match self.termination.try_start() {
+                    Some(reevaluation_start) => reevaluation_start,
+                    None => break,
+                };
+                result = self
+                    .sample_and_report(new_candidate.clone(), budget, self.elapsed_secs())
+                    .await;
+                reevaluation_start.completed = true;
+
+                let num_samples = samples
+                    .lock()
+                    .unwrap()
+                    .get(&new_candidate, budget)
+                    .map(|stats| stats.num_samples);
+                if num_samples == Some(num_samples_before) {
+                    break;
+                }
+            }
 
-        debug!("Iteration {} completed. Seen: {:?}", iter_num, seen);
+            let incumbent = {
+                let mut samples = samples.lock().unwrap();
+                let reevaluation_due = samples.on_evaluation();
+                samples
+                    .incumbent()
+                    .filter(|_| reevaluation_due)
+                    .map(|(incumbent, incumbent_budget, _)| (incumbent.clone(), incumbent_budget))
+            };
+            if let Some((incumbent, incumbent_budget)) = incumbent {
+                if let Some(mut reevaluation_start) = self.termination.try_start() {
+                    self.sample_and_report(incumbent, incumbent_budget, self.elapsed_secs())
+                        .await;
+                    reevaluation_start.completed = true;
+                }
+            }
+        }
+
+        result
     }
-}
 
-async fn evaluate_candidate_and_report(
-    obj_func_call_def: &ObjFuncCallDef,
-    new_candidate: serde_json::Value,
-    seen_context: SeenContext,
-    processing_start_instant: &AppTime,
-    iteration_start_time: f64,
-    event_sender: EventSender,
-) {
-    let new_obj_func_val_option = obj_func::call(obj_func_call_def, &new_candidate).await;
-    let completion_time = processing_start_instant
-        .elapsed()
-        .unwrap_or(Duration::ZERO)
-        .as_secs_f64();
-
-    let mut seen_option = seen_context.lock().unwrap();
-    let obj_func_val_before = seen_option.as_ref().map(|seen| seen.best_obj_func_val);
-    let latest_completion_time_before =
-        seen_option.as_ref().map(|seen| seen.latest_completion_time);
-
-    match new_obj_func_val_option {
-        Some(new_obj_func_val) => {
+    /// Calls the objective program once and reports the sample
+    async fn sample_and_report(
+        &self,
+        new_candidate: serde_json::Value,
+        budget: Option<f64>,
+        iteration_start_time: f64,
+    ) -> Option<ObjFuncChildResult> {
+        let result = obj_func::call(&self.obj_func_call_def, &new_candidate, budget).await;
+
+        if let (Some(cache), Some(result)) = (&self.cache, &result) {
+            cache
+                .lock()
+                .unwrap()
+                .insert(&new_candidate, budget, result.clone());
+        }
+
+        self.report(
+            new_candidate,
+            budget,
+            iteration_start_time,
+            result.as_ref(),
+            false,
+        );
+        result
+    }
+
+    /// Reports the result of an evaluation and keeps track of the best seen candidate. Samples
+    /// are only added, and termination criteria only checked, for results not taken from the
+    /// cache.
+    fn report(
+        &self,
+        new_candidate: serde_json::Value,
+        budget: Option<f64>,
+        iteration_start_time: f64,
+        result: Option<&ObjFuncChildResult>,
+        cache_hit: bool,
+    ) {
+        let new_obj_func_val_option = result.as_ref().and_then(|result| result.obj_func_val);
+        let constraint_vals = result
+            .as_ref()
+            .and_then(|result| result.constraint_vals.clone());
+        let feasible = total_violation(constraint_vals.as_deref().unwrap_or(&[])) == 0.0;
+        let completion_time = self.elapsed_secs();
+
+        let mut seen_option = self.seen.lock().unwrap();
+        let obj_func_val_before = seen_option.as_ref().map(|seen| seen.best_obj_func_val);
+        let latest_completion_time_before =
+            seen_option.as_ref().map(|seen| seen.latest_completion_time);
+
+        let mut sample_stats = None;
+
+        let improved = if let Some(samples) = &self.samples {
+            let mut samples = samples.lock().unwrap();
+            if let Some(new_obj_func_val) =
+                new_obj_func_val_option.filter(|_| feasible && !cache_hit)
+            {
+                let confidence_z = samples.conf.confidence_z;
+                let stats = samples.add(&new_candidate, budget, new_obj_func_val);
+                sample_stats = Some((
+                    stats.num_samples,
+                    stats.mean,
+                    stats.confidence_half_width(confidence_z),
+                ));
+            }
+
+            // The incumbent may change on any sample, since its own mean may deteriorate
+            match samples.incumbent() {
+                Some((incumbent, _, incumbent_mean)) => {
+                    *seen_option = Some(Seen {
+                        best_candidate: incumbent.clone(),
+                        best_obj_func_val: incumbent_mean,
+                        latest_completion_time: completion_time,
+                    });
+                    obj_func_val_before
+                        .map(|obj_func_val_before| {
+                            self.direction
+                                .is_better(incumbent_mean, obj_func_val_before)
+                        })
+                        .unwrap_or(true)
+                }
+                None => false,
+            }
+        } else if let Some(new_obj_func_val) = new_obj_func_val_option.filter(|_| feasible) {
             let replace = seen_option
                 .as_ref()
-                .map(|seen| new_obj_func_val < seen.best_obj_func_val)
+                .map(|seen| {
+                    self.direction
+                        .is_better(new_obj_func_val, seen.best_obj_func_val)
+                })
                 .unwrap_or(true);
 
             if replace {
@@ -146,44 +897,148 @@ async fn evaluate_candidate_and_report(
                     latest_completion_time: completion_time,
                 });
             }
+            replace
+        } else {
+            false
+        };
+
+        // Under noise handling, the target must be reached by the incumbent mean, not by a sample
+        let target_checked_obj_func_val = if self.samples.is_some() {
+            seen_option.as_ref().map(|seen| seen.best_obj_func_val)
+        } else {
+            new_obj_func_val_option.filter(|_| feasible)
+        };
+
+        if let Some(seen) = seen_option.as_mut() {
+            seen.latest_completion_time = completion_time;
         }
-        None => (),
-    };
 
-    seen_option.as_mut().unwrap().latest_completion_time = completion_time;
+        let latest_interleaving_completion_time = latest_completion_time_before
+            .filter(|completion_time_before| *completion_time_before > iteration_start_time);
 
-    let latest_interleaving_completion_time = latest_completion_time_before
-        .filter(|completion_time_before| *completion_time_before > iteration_start_time);
+        let report = CandidateEvalReport {
+            start_time: iteration_start_time,
+            start_unix_timestamp: self
+                .processing_start_instant
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or(Duration::ZERO)
+                .as_secs_f64()
+                + iteration_start_time,
+            completion_time,
+            obj_func_val: new_obj_func_val_option,
+            obj_func_vals: result
+                .as_ref()
+                .and_then(|result| result.obj_func_vals.clone()),
+            best_seen_obj_func_val_before: obj_func_val_before,
+            candidate: new_candidate,
+            budget,
+            constraint_vals,
+            feasible,
+            num_samples: sample_stats.map(|(num_samples, _, _)| num_samples),
+            mean_obj_func_val: sample_stats.map(|(_, mean, _)| mean),
+            confidence_half_width: sample_stats.and_then(|(_, _, half_width)| half_width),
+            imported: false,
+            island: self.island.as_ref().map(|island| island.number),
+            cache_hit,
+            latest_interleaving_completion_time,
+        };
 
-    let report = CandidateEvalReport {
-        start_time: iteration_start_time,
-        start_unix_timestamp: processing_start_instant
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap_or(Duration::ZERO)
-            .as_secs_f64()
-            + iteration_start_time,
-        completion_time,
-        obj_func_val: new_obj_func_val_option,
-        best_seen_obj_func_val_before: obj_func_val_before,
-        candidate: new_candidate.clone(),
-        latest_interleaving_completion_time,
-    };
+        self.event_sender
+            .send(AppEvent::DelegateStatusMessage(
+                StatusMessage::CandidateEvalReport(report),
+            ))
+            .ok();
 
-    event_sender
-        .send(AppEvent::DelegateStatusMessage(
-            StatusMessage::CandidateEvalReport(report),
-        ))
-        .ok();
+        if !cache_hit {
+            self.termination
+                .on_evaluation(target_checked_obj_func_val, result.is_none(), improved);
+        }
+    }
+}
+
+/// Identifies an evaluation by the candidate JSON and the budget. JSON maps are sorted by key,
+/// which makes the JSON string of a candidate canonical.
+fn evaluation_key(candidate: &serde_json::Value, budget: Option<f64>) -> String {
+    match budget {
+        Some(budget) => format!("{}@{}", candidate, budget),
+        None => candidate.to_string(),
+    }
+}
+
+/// Sorts from best to worst, failed evaluations last
+fn sort_evaluations(evaluations: &mut [Evaluation]) {
+    evaluations.sort_by(|a, b| {
+        sort_key(a.obj_func_val)
+            .partial_cmp(&sort_key(b.obj_func_val))
+            .unwrap()
+    });
+}
+
+/// The total violation of an infeasible candidate under feasibility rules, given its fitness
+fn infeasible_violation(fitness: f64) -> Option<f64> {
+    (fitness >= INFEASIBLE_FITNESS_BASE).then(|| fitness / INFEASIBLE_FITNESS_BASE - 1.0)
+}
+
+/// Orders objective function values to be minimised, failed evaluations last
+fn sort_key(obj_func_val: Option<f64>) -> f64 {
+    obj_func_val.unwrap_or(f64::INFINITY)
+}
+
+fn is_feasible(result: &ObjFuncChildResult) -> bool {
+    total_violation(result.constraint_vals.as_deref().unwrap_or(&[])) == 0.0
+}
+
+/// A uniformly distributed point of the unit cube of the spec
+fn random_unit_cube_point(spec: &ParamsSpec, rng: &mut StdRng) -> Vec<f64> {
+    spec.dims.iter().map(|_| rng.gen()).collect()
+}
+
+/// A uniformly distributed candidate. Each value of an integer dim is equally likely.
+fn random_candidate(spec: &ParamsSpec, rng: &mut StdRng) -> ParamsValue {
+    let mut result = ParamsValue::new();
+
+    for dim in &spec.dims {
+        match dim {
+            Dim::Boolean(dim_spec) => {
+                result.insert(dim_spec.name.clone(), Bool(rng.gen()));
+            }
+            Dim::RealNumber(dim_spec_with_bounds) => {
+                let min_value = dim_spec_with_bounds.min_value_incl;
+                let value = min_value
+                    + rng.gen::<f64>() * (dim_spec_with_bounds.max_value_excl - min_value);
+                result.insert(
+                    dim_spec_with_bounds.dim_spec.name.clone(),
+                    Number(NumberValue::from_f64(value).unwrap()),
+                );
+            }
+            Dim::Integer(dim_spec_with_bounds) => {
+                let min_value = dim_spec_with_bounds.min_value_incl;
+                let max_value = dim_spec_with_bounds.max_value_excl.max(min_value);
+                result.insert(
+                    dim_spec_with_bounds.dim_spec.name.clone(),
+                    Number(NumberValue::from(rng.gen_range(min_value..=max_value))),
+                );
+            }
+        }
+    }
+
+    result
+}
+
+/// Moves a unit cube point to the point of the candidate it represents, e.g. onto the lattice of
+/// an integer dim
+fn snap(spec: &ParamsSpec, point: &[f64]) -> Vec<f64> {
+    spec.to_unit_cube(&spec.from_unit_cube(point))
 }
 
 fn create_candidate(
     from_candidate: &ParamsValue,
     params_spec: &ParamsSpec,
-    conf: &ParallelHillClimbingConf,
+    relative_std_dev: f64,
     rng: &mut StdRng,
 ) -> ParamsValue {
     let mut result = ParamsValue::default();
-    let std_dev = conf.relative_std_dev;
+    let std_dev = relative_std_dev;
     for dim_spec in &params_spec.dims {
         match dim_spec {
             Dim::Boolean(bool_spec) => {
@@ -224,7 +1079,7 @@ fn create_candidate(
                 let result_value = Normal::new(from_value as f64, stdev_to_use)
                     .unwrap()
                     .sample(rng);
-                let result_value = (result_value as i64)
+                let result_value = (result_value.round() as i64)
                     .min(int_spec.max_value_excl)
                     .max(int_spec.min_value_incl);
                 result.insert(
@@ -236,3 +1091,317 @@ fn create_candidate(
     }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::type_aliases::EventReceiver;
+    use serde_json::json;
+    use tokio::sync::mpsc;
+
+    fn termination(conf: TerminationConf, direction: ObjectiveDirection) -> Termination {
+        Termination::new(conf, direction)
+    }
+
+    fn reason(termination: &Termination) -> Option<TerminationReason> {
+        *termination.reason.lock().unwrap()
+    }
+
+    fn ctx(objective_conf: ObjectiveConf) -> ProcessingContext {
+        ctx_and_events(objective_conf).0
+    }
+
+    fn ctx_and_events(objective_conf: ObjectiveConf) -> (ProcessingContext, EventReceiver) {
+        let spec = ParamsSpec::from_json(json!({
+            "definition": {"x": [-10, 10]},
+            "initial_guess": {"x": 0.0},
+        }))
+        .unwrap();
+        let (event_sender, event_receiver) = mpsc::unbounded_channel();
+
+        let ctx = ProcessingContext::new(
+            SystemTime::now(),
+            ObjFuncCallDef {
+                program: String::new(),
+                args: Vec::new(),
+            },
+            TerminationConf::default(),
+            objective_conf,
+            event_sender,
+            &spec,
+            Vec::new(),
+        );
+        (ctx, event_receiver)
+    }
+
+    fn result(obj_func_val: f64, constraint_vals: &[f64]) -> Option<ObjFuncChildResult> {
+        Some(ObjFuncChildResult {
+            obj_func_val: Some(obj_func_val),
+            obj_func_vals: None,
+            constraint_vals: Some(constraint_vals.to_vec()),
+        })
+    }
+
+    #[test]
+    fn max_evaluations_limit_the_started_evaluations() {
+        let termination = termination(
+            TerminationConf {
+                max_evaluations: Some(2),
+                ..TerminationConf::default()
+            },
+            ObjectiveDirection::Minimize,
+        );
+
+        let mut first = termination.try_start().unwrap();
+        let second = termination.try_start().unwrap();
+        assert!(termination.try_start().is_none());
+
+        // An evaluation dropped before completion is given back
+        drop(second);
+        let mut second = termination.try_start().unwrap();
+
+        first.completed = true;
+        second.completed = true;
+        drop((first, second));
+        assert!(termination.try_start().is_none());
+
+        termination.on_evaluation(Some(1.0), false, true);
+        assert_eq!(reason(&termination), None);
+        termination.on_evaluation(Some(2.0), false, false);
+        assert_eq!(
+            reason(&termination),
+            Some(TerminationReason::MaxEvaluations)
+        );
+    }
+
+    #[tokio::test]
+    async fn max_evaluations_of_0_are_reached_right_away() {
+        let termination = termination(
+            TerminationConf {
+                max_evaluations: Some(0),
+                ..TerminationConf::default()
+            },
+            ObjectiveDirection::Minimize,
+        );
+
+        assert!(termination.try_start().is_none());
+        assert_eq!(
+            termination.reached(0.0).await,
+            TerminationReason::MaxEvaluations
+        );
+    }
+
+    #[test]
+    fn target_is_reached_by_values_at_least_as_good() {
+        for (direction, not_reaching, reaching) in [
+            (ObjectiveDirection::Minimize, 1.5, 1.0),
+            (ObjectiveDirection::Maximize, 0.5, 1.0),
+        ] {
+            let termination = termination(
+                TerminationConf {
+                    target_obj_func_val: Some(1.0),
+                    ..TerminationConf::default()
+                },
+                direction,
+            );
+
+            termination.on_evaluation(Some(not_reaching), false, true);
+            termination.on_evaluation(None, true, false);
+            assert_eq!(reason(&termination), None);
+            termination.on_evaluation(Some(reaching), false, true);
+            assert_eq!(
+                reason(&termination),
+                Some(TerminationReason::TargetObjFuncVal)
+            );
+        }
+    }
+
+    #[test]
+    fn iterations_without_improvement_are_counted_since_the_latest_improvement() {
+        let termination = termination(
+            TerminationConf {
+                max_iterations_without_improvement: Some(2),
+                ..TerminationConf::default()
+            },
+            ObjectiveDirection::Minimize,
+        );
+
+        termination.on_evaluation(Some(2.0), false, true);
+        termination.on_iteration();
+        termination.on_iteration();
+        termination.on_evaluation(Some(1.0), false, true);
+        termination.on_evaluation(Some(3.0), false, false);
+        termination.on_iteration();
+        termination.on_iteration();
+        assert_eq!(reason(&termination), None);
+        termination.on_iteration();
+        assert_eq!(
+            reason(&termination),
+            Some(TerminationReason::MaxIterationsWithoutImprovement)
+        );
+    }
+
+    #[test]
+    fn consecutive_failures_are_reset_by_a_success() {
+        let termination = termination(
+            TerminationConf {
+                max_consecutive_failures: Some(2),
+                ..TerminationConf::default()
+            },
+            ObjectiveDirection::Minimize,
+        );
+
+        termination.on_evaluation(None, true, false);
+        termination.on_evaluation(Some(1.0), false, true);
+        termination.on_evaluation(None, true, false);
+        assert_eq!(reason(&termination), None);
+        termination.on_evaluation(None, true, false);
+        assert_eq!(
+            reason(&termination),
+            Some(TerminationReason::MaxConsecutiveFailures)
+        );
+    }
+
+    #[tokio::test]
+    async fn reached_returns_the_first_criterion_met() {
+        let termination = termination(
+            TerminationConf {
+                max_evaluations: Some(1),
+                target_obj_func_val: Some(1.0),
+                ..TerminationConf::default()
+            },
+            ObjectiveDirection::Minimize,
+        );
+
+        termination.on_evaluation(Some(0.5), false, true);
+        termination.terminate(TerminationReason::MaxWallTime);
+        assert_eq!(
+            termination.reached(0.0).await,
+            TerminationReason::TargetObjFuncVal
+        );
+    }
+
+    #[test]
+    fn maximized_values_are_negated_for_algorithms() {
+        let ctx = ctx(ObjectiveConf {
+            direction: ObjectiveDirection::Maximize,
+            ..ObjectiveConf::default()
+        });
+
+        assert_eq!(ctx.fitness(result(3.0, &[])), Some(-3.0));
+        assert_eq!(ctx.fitness(None), None);
+        assert!(ObjectiveDirection::Maximize.is_better(3.0, 2.0));
+        assert!(ObjectiveDirection::Minimize.is_better(2.0, 3.0));
+        assert!(!ObjectiveDirection::Maximize.is_better(3.0, 3.0));
+    }
+
+    #[test]
+    fn infeasible_candidates_rank_above_feasible_ones_by_total_violation() {
+        for direction in [ObjectiveDirection::Minimize, ObjectiveDirection::Maximize] {
+            let ctx = ctx(ObjectiveConf {
+                direction,
+                ..ObjectiveConf::default()
+            });
+
+            let feasible = ctx.fitness(result(-1e6, &[0.0, -5.0])).unwrap();
+            let slightly_infeasible = ctx.fitness(result(0.0, &[0.1, -5.0])).unwrap();
+            let infeasible = ctx.fitness(result(0.0, &[0.1, 1.9])).unwrap();
+
+            assert!(feasible < slightly_infeasible);
+            assert!(slightly_infeasible < infeasible);
+            assert_eq!(infeasible, INFEASIBLE_FITNESS_BASE * 3.0);
+        }
+    }
+
+    #[test]
+    fn penalty_adds_the_weighted_total_violation() {
+        let ctx = ctx(ObjectiveConf {
+            direction: ObjectiveDirection::Maximize,
+            constraint_handling: ConstraintHandling::Penalty { coefficient: 10.0 },
+            ..ObjectiveConf::default()
+        });
+
+        assert_eq!(ctx.fitness(result(5.0, &[-1.0])), Some(-5.0));
+        assert_eq!(ctx.fitness(result(5.0, &[0.5, 1.0])), Some(10.0));
+    }
+
+    #[test]
+    fn cache_policy_determines_caching_and_merging() {
+        let noise_handling = Some(NoiseHandlingConf {
+            min_samples: 3,
+            reevaluation_interval: 0,
+            confidence_z: 1.0,
+        });
+        for (cache_policy, noise_handling, cached, merged) in [
+            (CachePolicy::Disabled, None, false, false),
+            (CachePolicy::NeverReevaluate, noise_handling, true, true),
+            (CachePolicy::ReevaluateIfNoisy, None, true, false),
+            (CachePolicy::ReevaluateIfNoisy, noise_handling, false, true),
+            (CachePolicy::MergeSamples, None, false, true),
+        ] {
+            let ctx = ctx(ObjectiveConf {
+                cache_policy,
+                noise_handling,
+                ..ObjectiveConf::default()
+            });
+
+            assert_eq!(ctx.cache.is_some(), cached, "{:?}", cache_policy);
+            assert_eq!(ctx.samples.is_some(), merged, "{:?}", cache_policy);
+        }
+    }
+
+    #[tokio::test]
+    async fn cache_hits_are_reported_once_and_not_counted_as_evaluations() {
+        let (ctx, mut event_receiver) = ctx_and_events(ObjectiveConf {
+            cache_policy: CachePolicy::NeverReevaluate,
+            ..ObjectiveConf::default()
+        });
+        let candidate = json!({"x": 1.0});
+        ctx.cache.as_ref().unwrap().lock().unwrap().insert(
+            &candidate,
+            None,
+            result(2.0, &[]).unwrap(),
+        );
+
+        for _ in 0..2 {
+            let result = ctx
+                .evaluate_candidate_and_report(candidate.clone(), None, 0.0)
+                .await;
+            assert_eq!(result.and_then(|result| result.obj_func_val), Some(2.0));
+        }
+
+        match event_receiver.try_recv() {
+            Ok(AppEvent::DelegateStatusMessage(StatusMessage::CandidateEvalReport(report))) => {
+                assert!(report.cache_hit);
+                assert_eq!(report.obj_func_val, Some(2.0));
+            }
+            event => panic!("Expected a candidate eval report, got {:?}", event),
+        }
+        assert!(event_receiver.try_recv().is_err());
+
+        let counts = ctx.termination.counts.lock().unwrap();
+        assert_eq!(counts.num_started, 0);
+        assert_eq!(counts.num_evaluations, 0);
+    }
+
+    #[test]
+    fn merged_samples_give_the_mean_of_duplicates() {
+        let ctx = ctx(ObjectiveConf {
+            cache_policy: CachePolicy::MergeSamples,
+            ..ObjectiveConf::default()
+        });
+        let candidate = json!({"x": 1.0});
+
+        for obj_func_val in [1.0, 3.0] {
+            let result = result(obj_func_val, &[]);
+            ctx.report(candidate.clone(), None, 0.0, result.as_ref(), false);
+        }
+
+        let samples = ctx.samples.as_ref().unwrap().lock().unwrap();
+        let stats = samples.get(&candidate, None).unwrap();
+        assert_eq!(stats.num_samples, 2);
+        assert_eq!(stats.mean, 2.0);
+        drop(samples);
+        assert_eq!(ctx.best_obj_func_val(), Some(2.0));
+    }
+}
@@ -0,0 +1,40 @@
+use super::ProcessingContext;
+use crate::optimizer::Optimizer;
+use futures::stream::{FuturesUnordered, StreamExt};
+use log::debug;
+use serde_json::Value::Object;
+
+/// Keeps up to `degree_of_par` evaluations in flight, asking the optimizer for new candidates
+/// whenever slots are free and telling it each result as soon as the evaluation completes.
+/// Terminates once the optimizer proposes no candidates while none are in flight.
+pub(super) async fn run(
+    ctx: &ProcessingContext,
+    mut optimizer: Box<dyn Optimizer>,
+    degree_of_par: usize,
+) {
+    let degree_of_par = degree_of_par.max(1);
+    let mut in_flight = FuturesUnordered::new();
+
+    loop {
+        let num_free_slots = degree_of_par - in_flight.len();
+        if num_free_slots > 0 {
+            for candidate in optimizer
+                .ask(num_free_slots)
+                .into_iter()
+                .take(num_free_slots)
+            {
+                in_flight.push(ctx.evaluate_tagged(candidate.clone(), Object(candidate)));
+            }
+        }
+
+        match in_flight.next().await {
+            Some((candidate, obj_func_val)) => {
+                optimizer.tell(candidate, obj_func_val);
+                debug!("Evaluation completed. Seen: {:?}", ctx.seen);
+            }
+            None => break,
+        }
+    }
+
+    debug!("Optimizer exhausted. Seen: {:?}", ctx.seen);
+}
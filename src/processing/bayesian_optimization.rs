@@ -0,0 +1,346 @@
+use super::stats::{std_normal_cdf, std_normal_pdf};
+use super::{infeasible_violation, random_unit_cube_point, snap, ProcessingContext};
+use crate::algo::{AcquisitionFunction, BayesianOptimizationConf, ConstantLiar};
+use crate::param::ParamsSpec;
+use log::debug;
+use nalgebra::{Cholesky, DMatrix, DVector, Dyn};
+use rand::{rngs::StdRng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+use serde_json::Value::Object;
+use std::f64::consts::PI;
+use std::sync::Arc;
+use tokio::task;
+
+const LENGTH_SCALE_CANDIDATES: [f64; 6] = [0.05, 0.1, 0.2, 0.35, 0.5, 1.0];
+const NOISE_VARIANCE: f64 = 1e-6;
+const NUM_RANDOM_ACQUISITION_SAMPLES: usize = 1000;
+const NUM_LOCAL_ACQUISITION_SAMPLES: usize = 20;
+const NUM_LOCAL_ACQUISITION_CENTERS: usize = 5;
+const LOCAL_ACQUISITION_STD_DEV: f64 = 0.05;
+
+/// Gaussian process with a squared exponential kernel over the unit cube, fitted to standardized
+/// objective function values.
+struct GaussianProcess {
+    points: Vec<Vec<f64>>,
+    length_scale: f64,
+    y_mean: f64,
+    y_std_dev: f64,
+    cholesky: Cholesky<f64, Dyn>,
+    alpha: DVector<f64>,
+}
+
+impl GaussianProcess {
+    /// Fits with the length scale that maximizes the log marginal likelihood.
+    fn fit(points: &[Vec<f64>], obj_func_vals: &[f64]) -> Option<GaussianProcess> {
+        LENGTH_SCALE_CANDIDATES
+            .iter()
+            .filter_map(|&length_scale| {
+                GaussianProcess::fit_with_length_scale(points, obj_func_vals, length_scale)
+            })
+            .map(|gp| (gp.log_marginal_likelihood(), gp))
+            .max_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap())
+            .map(|(_, gp)| gp)
+    }
+
+    fn fit_with_length_scale(
+        points: &[Vec<f64>],
+        obj_func_vals: &[f64],
+        length_scale: f64,
+    ) -> Option<GaussianProcess> {
+        let num_points = points.len();
+        let y_mean = obj_func_vals.iter().sum::<f64>() / num_points as f64;
+        let y_var = obj_func_vals
+            .iter()
+            .map(|y| (y - y_mean).powi(2))
+            .sum::<f64>()
+            / num_points as f64;
+        let y_std_dev = if y_var > 0.0 { y_var.sqrt() } else { 1.0 };
+
+        let y = DVector::from_iterator(
+            num_points,
+            obj_func_vals.iter().map(|y| (y - y_mean) / y_std_dev),
+        );
+        let kernel_matrix = DMatrix::from_fn(num_points, num_points, |i, j| {
+            kernel(&points[i], &points[j], length_scale) + if i == j { NOISE_VARIANCE } else { 0.0 }
+        });
+
+        let cholesky = kernel_matrix.cholesky()?;
+        let alpha = cholesky.solve(&y);
+
+        Some(GaussianProcess {
+            points: points.to_vec(),
+            length_scale,
+            y_mean,
+            y_std_dev,
+            cholesky,
+            alpha,
+        })
+    }
+
+    fn log_marginal_likelihood(&self) -> f64 {
+        let num_points = self.points.len() as f64;
+        let y = self.cholesky.l() * self.cholesky.l().transpose() * &self.alpha;
+        -0.5 * y.dot(&self.alpha)
+            - self.cholesky.l().diagonal().map(|d| d.ln()).sum()
+            - 0.5 * num_points * (2.0 * PI).ln()
+    }
+
+    /// Predictive mean and standard deviation in units of the objective function
+    fn predict(&self, point: &[f64]) -> (f64, f64) {
+        let k_star = DVector::from_iterator(
+            self.points.len(),
+            self.points
+                .iter()
+                .map(|other| kernel(point, other, self.length_scale)),
+        );
+        let mean = k_star.dot(&self.alpha);
+        let v = self
+            .cholesky
+            .l()
+            .solve_lower_triangular(&k_star)
+            .unwrap_or_else(|| DVector::zeros(self.points.len()));
+        let var = (1.0 - v.dot(&v)).max(0.0);
+
+        (
+            self.y_mean + mean * self.y_std_dev,
+            var.sqrt() * self.y_std_dev,
+        )
+    }
+}
+
+fn kernel(a: &[f64], b: &[f64], length_scale: f64) -> f64 {
+    let squared_dist: f64 = a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum();
+    (-0.5 * squared_dist / (length_scale * length_scale)).exp()
+}
+
+pub(super) async fn run(
+    ctx: &ProcessingContext,
+    spec: ParamsSpec,
+    algo_conf: BayesianOptimizationConf,
+) {
+    let spec = Arc::new(spec);
+    let initial_guess = spec.extract_initial_guess();
+
+    debug!("Starting with initial guess: {:?}", &initial_guess);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let degree_of_par = algo_conf.degree_of_par.max(1);
+
+    let mut points: Vec<Vec<f64>> = Vec::new();
+    let mut obj_func_vals: Vec<f64> = Vec::new();
+
+    // Imported evaluations take the place of initial samples, the initial guess included, which
+    // is then the best imported candidate
+    for imported in &ctx.imported {
+        if let Some(obj_func_val) = imported.obj_func_val {
+            points.push(spec.to_unit_cube(&imported.candidate));
+            obj_func_vals.push(obj_func_val);
+        }
+    }
+    let warm_started = !points.is_empty();
+
+    let num_initial_samples = if warm_started {
+        algo_conf.num_initial_samples.saturating_sub(points.len())
+    } else {
+        algo_conf.num_initial_samples.max(1)
+    };
+
+    let initial_points: Vec<Vec<f64>> = (0..num_initial_samples)
+        .map(|sample_number| {
+            if sample_number == 0 && !warm_started {
+                spec.to_unit_cube(&initial_guess)
+            } else {
+                snap(&spec, &random_unit_cube_point(&spec, &mut rng))
+            }
+        })
+        .collect();
+
+    for batch in initial_points.chunks(degree_of_par) {
+        evaluate_and_record(ctx, &spec, batch, &mut points, &mut obj_func_vals).await;
+    }
+
+    for iter_num in 0.. {
+        // Migrants of the island model are learned from, like imported evaluations
+        for migrant in ctx.migrants() {
+            if let Some(obj_func_val) = migrant.obj_func_val {
+                points.push(spec.to_unit_cube(&migrant.candidate));
+                obj_func_vals.push(obj_func_val);
+            }
+        }
+
+        // Fitting and acquisition maximization are CPU bound and must not block the runtime
+        let (batch, returned_rng) = {
+            let spec = spec.clone();
+            let points = points.clone();
+            let obj_func_vals = obj_func_vals.clone();
+            let algo_conf = algo_conf.clone();
+            task::spawn_blocking(move || {
+                let batch = propose_batch(
+                    &spec,
+                    &points,
+                    &obj_func_vals,
+                    degree_of_par,
+                    &algo_conf,
+                    &mut rng,
+                );
+                (batch, rng)
+            })
+            .await
+            .unwrap()
+        };
+        rng = returned_rng;
+
+        evaluate_and_record(ctx, &spec, &batch, &mut points, &mut obj_func_vals).await;
+
+        debug!("Iteration {} completed. Seen: {:?}", iter_num, ctx.seen);
+    }
+}
+
+async fn evaluate_and_record(
+    ctx: &ProcessingContext,
+    spec: &ParamsSpec,
+    batch: &[Vec<f64>],
+    points: &mut Vec<Vec<f64>>,
+    obj_func_vals: &mut Vec<f64>,
+) {
+    let candidates = batch
+        .iter()
+        .map(|point| Object(spec.from_unit_cube(point)))
+        .collect();
+
+    for (point, obj_func_val) in batch.iter().zip(ctx.evaluate(candidates).await) {
+        if let Some(obj_func_val) = obj_func_val {
+            points.push(point.clone());
+            obj_func_vals.push(obj_func_val);
+        }
+    }
+}
+
+/// Proposes a batch using the constant liar strategy: each proposed point is added to the
+/// training data with the lie as its objective function value before the next one is proposed.
+fn propose_batch(
+    spec: &ParamsSpec,
+    points: &[Vec<f64>],
+    obj_func_vals: &[f64],
+    batch_size: usize,
+    algo_conf: &BayesianOptimizationConf,
+    rng: &mut StdRng,
+) -> Vec<Vec<f64>> {
+    let mut points = points.to_vec();
+    let mut obj_func_vals = surrogate_obj_func_vals(obj_func_vals);
+
+    let lie = if obj_func_vals.is_empty() {
+        0.0
+    } else {
+        match algo_conf.constant_liar {
+            ConstantLiar::Min => obj_func_vals.iter().cloned().fold(f64::INFINITY, f64::min),
+            ConstantLiar::Mean => obj_func_vals.iter().sum::<f64>() / obj_func_vals.len() as f64,
+            ConstantLiar::Max => obj_func_vals
+                .iter()
+                .cloned()
+                .fold(f64::NEG_INFINITY, f64::max),
+        }
+    };
+
+    let mut batch = Vec::with_capacity(batch_size);
+
+    for _ in 0..batch_size {
+        let gp = if points.is_empty() {
+            None
+        } else {
+            GaussianProcess::fit(&points, &obj_func_vals)
+        };
+
+        let proposal = match gp {
+            Some(gp) => {
+                let best_obj_func_val = obj_func_vals.iter().cloned().fold(f64::INFINITY, f64::min);
+                let acquisition = |point: &[f64]| {
+                    let (mean, std_dev) = gp.predict(point);
+                    acquisition_value(&algo_conf.acquisition, mean, std_dev, best_obj_func_val)
+                };
+
+                acquisition_samples(spec, &points, &obj_func_vals, rng)
+                    .into_iter()
+                    .map(|point| (acquisition(&point), point))
+                    .max_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap())
+                    .map(|(_, point)| point)
+                    .unwrap()
+            }
+            None => snap(spec, &random_unit_cube_point(spec, rng)),
+        };
+
+        points.push(proposal.clone());
+        obj_func_vals.push(lie);
+        batch.push(proposal);
+    }
+
+    batch
+}
+
+/// Infeasible candidates are modelled as the worst feasible one plus their total violation, which
+/// keeps the values the Gaussian process is fitted to on the scale of the feasible ones
+fn surrogate_obj_func_vals(obj_func_vals: &[f64]) -> Vec<f64> {
+    let worst_feasible_obj_func_val = obj_func_vals
+        .iter()
+        .cloned()
+        .filter(|&obj_func_val| infeasible_violation(obj_func_val).is_none())
+        .reduce(f64::max)
+        .unwrap_or(0.0);
+
+    obj_func_vals
+        .iter()
+        .map(|&obj_func_val| match infeasible_violation(obj_func_val) {
+            Some(violation) => worst_feasible_obj_func_val + violation,
+            None => obj_func_val,
+        })
+        .collect()
+}
+
+/// Random points in the unit cube plus perturbations of the best points seen so far
+fn acquisition_samples(
+    spec: &ParamsSpec,
+    points: &[Vec<f64>],
+    obj_func_vals: &[f64],
+    rng: &mut StdRng,
+) -> Vec<Vec<f64>> {
+    let mut samples: Vec<Vec<f64>> = (0..NUM_RANDOM_ACQUISITION_SAMPLES)
+        .map(|_| snap(spec, &random_unit_cube_point(spec, rng)))
+        .collect();
+
+    let mut ranking: Vec<usize> = (0..points.len()).collect();
+    ranking.sort_by(|&a, &b| obj_func_vals[a].partial_cmp(&obj_func_vals[b]).unwrap());
+
+    let perturbation = Normal::new(0.0, LOCAL_ACQUISITION_STD_DEV).unwrap();
+    for &index in ranking.iter().take(NUM_LOCAL_ACQUISITION_CENTERS) {
+        for _ in 0..NUM_LOCAL_ACQUISITION_SAMPLES {
+            let point: Vec<f64> = points[index]
+                .iter()
+                .map(|coord| coord + perturbation.sample(rng))
+                .collect();
+            samples.push(snap(spec, &point));
+        }
+    }
+
+    samples
+}
+
+/// Larger is better. Both acquisition functions assume minimization.
+fn acquisition_value(
+    acquisition: &AcquisitionFunction,
+    mean: f64,
+    std_dev: f64,
+    best_obj_func_val: f64,
+) -> f64 {
+    match acquisition {
+        AcquisitionFunction::ExpectedImprovement { exploration } => {
+            let improvement = best_obj_func_val - mean - exploration;
+            if std_dev > 0.0 {
+                let z = improvement / std_dev;
+                improvement * std_normal_cdf(z) + std_dev * std_normal_pdf(z)
+            } else {
+                improvement.max(0.0)
+            }
+        }
+        AcquisitionFunction::UpperConfidenceBound { beta } => -(mean - beta.sqrt() * std_dev),
+    }
+}
@@ -0,0 +1,44 @@
+use super::evaluation_key;
+use crate::obj_func::ObjFuncChildResult;
+use std::collections::HashMap;
+
+#[derive(Debug)]
+struct CacheEntry {
+    result: ObjFuncChildResult,
+    hit: bool,
+}
+
+/// Results of successful evaluations, keyed by the candidate JSON and the budget
+#[derive(Debug, Default)]
+pub(super) struct EvaluationCache {
+    results: HashMap<String, CacheEntry>,
+}
+
+impl EvaluationCache {
+    /// Looks up the result of an earlier evaluation, along with whether this is its first hit
+    pub fn hit(
+        &mut self,
+        candidate: &serde_json::Value,
+        budget: Option<f64>,
+    ) -> Option<(ObjFuncChildResult, bool)> {
+        self.results
+            .get_mut(&evaluation_key(candidate, budget))
+            .map(|entry| {
+                let first_hit = !entry.hit;
+                entry.hit = true;
+                (entry.result.clone(), first_hit)
+            })
+    }
+
+    pub fn insert(
+        &mut self,
+        candidate: &serde_json::Value,
+        budget: Option<f64>,
+        result: ObjFuncChildResult,
+    ) {
+        self.results.insert(
+            evaluation_key(candidate, budget),
+            CacheEntry { result, hit: false },
+        );
+    }
+}
@@ -0,0 +1,245 @@
+use super::{random_unit_cube_point, sort_key, ProcessingContext};
+use crate::algo::CmaEsConf;
+use crate::param::ParamsSpec;
+use log::debug;
+use nalgebra::{DMatrix, DVector};
+use rand::{rngs::StdRng, SeedableRng};
+use rand_distr::{Distribution, StandardNormal};
+use serde_json::Value::Object;
+
+/// Strategy parameters of a (mu/mu_w, lambda)-CMA-ES, see Hansen, "The CMA Evolution Strategy:
+/// A Tutorial".
+struct StrategyParams {
+    weights: Vec<f64>,
+    mu_eff: f64,
+    c_c: f64,
+    c_sigma: f64,
+    c_1: f64,
+    c_mu: f64,
+    d_sigma: f64,
+    chi_n: f64,
+}
+
+impl StrategyParams {
+    fn new(num_dims: usize, population_size: usize) -> StrategyParams {
+        let n = num_dims as f64;
+        let mu = population_size / 2;
+
+        let raw_weights: Vec<f64> = (1..=mu)
+            .map(|i| (mu as f64 + 0.5).ln() - (i as f64).ln())
+            .collect();
+        let weight_sum: f64 = raw_weights.iter().sum();
+        let weights: Vec<f64> = raw_weights.iter().map(|w| w / weight_sum).collect();
+        let mu_eff = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+
+        let c_c = (4.0 + mu_eff / n) / (n + 4.0 + 2.0 * mu_eff / n);
+        let c_sigma = (mu_eff + 2.0) / (n + mu_eff + 5.0);
+        let c_1 = 2.0 / ((n + 1.3).powi(2) + mu_eff);
+        let c_mu =
+            (1.0 - c_1).min(2.0 * (mu_eff - 2.0 + 1.0 / mu_eff) / ((n + 2.0).powi(2) + mu_eff));
+        let d_sigma = 1.0 + 2.0 * (((mu_eff - 1.0) / (n + 1.0)).sqrt() - 1.0).max(0.0) + c_sigma;
+        let chi_n = n.sqrt() * (1.0 - 1.0 / (4.0 * n) + 1.0 / (21.0 * n * n));
+
+        StrategyParams {
+            weights,
+            mu_eff,
+            c_c,
+            c_sigma,
+            c_1,
+            c_mu,
+            d_sigma,
+            chi_n,
+        }
+    }
+}
+
+/// Adaptation state, living in the unit cube spanned by the spec bounds.
+struct State {
+    mean: DVector<f64>,
+    sigma: f64,
+    cov: DMatrix<f64>,
+    p_c: DVector<f64>,
+    p_sigma: DVector<f64>,
+    eigen_basis: DMatrix<f64>,
+    eigen_sqrt: DVector<f64>,
+}
+
+impl State {
+    fn new(mean: DVector<f64>, sigma: f64) -> State {
+        let num_dims = mean.len();
+        State {
+            mean,
+            sigma,
+            cov: DMatrix::identity(num_dims, num_dims),
+            p_c: DVector::zeros(num_dims),
+            p_sigma: DVector::zeros(num_dims),
+            eigen_basis: DMatrix::identity(num_dims, num_dims),
+            eigen_sqrt: DVector::from_element(num_dims, 1.0),
+        }
+    }
+
+    fn update_eigen_decomposition(&mut self) {
+        self.cov = (&self.cov + self.cov.transpose()) * 0.5;
+        let eigen = self.cov.clone().symmetric_eigen();
+        self.eigen_basis = eigen.eigenvectors;
+        self.eigen_sqrt = eigen
+            .eigenvalues
+            .map(|value| value.max(f64::EPSILON).sqrt());
+    }
+
+    fn inv_sqrt_cov(&self) -> DMatrix<f64> {
+        &self.eigen_basis
+            * DMatrix::from_diagonal(&self.eigen_sqrt.map(|value| 1.0 / value))
+            * self.eigen_basis.transpose()
+    }
+}
+
+/// With restarts configured, each restart begins from a uniformly random mean with fresh
+/// adaptation state, and the population size grows by the population growth factor (IPOP).
+/// Generations larger than `degree_of_par` are evaluated in batches.
+pub(super) async fn run(ctx: &ProcessingContext, spec: ParamsSpec, algo_conf: CmaEsConf) {
+    let initial_guess = spec.extract_initial_guess();
+
+    debug!("Starting with initial guess: {:?}", &initial_guess);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let num_dims = spec.dims.len();
+    let degree_of_par = algo_conf.degree_of_par.max(1);
+    let mut population_size = algo_conf.degree_of_par.max(2);
+    let mut initial_mean = DVector::from_vec(spec.to_unit_cube(&initial_guess));
+
+    for restart_num in 0.. {
+        let params = StrategyParams::new(num_dims, population_size);
+        let mut state = State::new(initial_mean, algo_conf.initial_relative_std_dev);
+
+        let mut best_obj_func_val: Option<f64> = None;
+        let mut evals_without_improvement = 0;
+
+        for generation in 0.. {
+            let points: Vec<DVector<f64>> = (0..population_size)
+                .map(|candidate_number| {
+                    if generation == 0 && candidate_number == 0 {
+                        state.mean.clone()
+                    } else {
+                        let z = DVector::from_fn(num_dims, |_, _| StandardNormal.sample(&mut rng));
+                        let y = &state.eigen_basis * z.component_mul(&state.eigen_sqrt);
+                        (&state.mean + y * state.sigma).map(|coord| coord.clamp(0.0, 1.0))
+                    }
+                })
+                .collect();
+
+            let mut obj_func_vals = Vec::with_capacity(population_size);
+            for batch in points.chunks(degree_of_par) {
+                let candidates = batch
+                    .iter()
+                    .map(|point| Object(spec.from_unit_cube(point.as_slice())))
+                    .collect();
+                obj_func_vals.extend(ctx.evaluate(candidates).await);
+            }
+
+            for obj_func_val in &obj_func_vals {
+                match obj_func_val {
+                    Some(obj_func_val)
+                        if best_obj_func_val
+                            .map(|best_obj_func_val| *obj_func_val < best_obj_func_val)
+                            .unwrap_or(true) =>
+                    {
+                        best_obj_func_val = Some(*obj_func_val);
+                        evals_without_improvement = 0;
+                    }
+                    _ => evals_without_improvement += 1,
+                }
+            }
+
+            let mut ranking: Vec<usize> = (0..population_size).collect();
+            ranking.sort_by(|&a, &b| {
+                sort_key(obj_func_vals[a])
+                    .partial_cmp(&sort_key(obj_func_vals[b]))
+                    .unwrap()
+            });
+
+            update(&mut state, &params, &points, &ranking, generation);
+
+            debug!(
+                "Generation {} of run {} completed with sigma {}. Seen: {:?}",
+                generation, restart_num, state.sigma, ctx.seen
+            );
+
+            if let Some(restart_conf) = &algo_conf.restart {
+                if evals_without_improvement >= restart_conf.evaluations_without_improvement {
+                    population_size = ((population_size as f64
+                        * restart_conf.population_growth_factor)
+                        .round() as usize)
+                        .max(2);
+                    break;
+                }
+            }
+        }
+
+        initial_mean = DVector::from_vec(random_unit_cube_point(&spec, &mut rng));
+        debug!(
+            "Restart {} with population size {}",
+            restart_num + 1,
+            population_size
+        );
+        ctx.report_restart(restart_num + 1, population_size);
+    }
+}
+
+fn update(
+    state: &mut State,
+    params: &StrategyParams,
+    points: &[DVector<f64>],
+    ranking: &[usize],
+    generation: usize,
+) {
+    let num_dims = state.mean.len();
+    let old_mean = state.mean.clone();
+
+    let steps: Vec<DVector<f64>> = ranking
+        .iter()
+        .take(params.weights.len())
+        .map(|&index| (&points[index] - &old_mean) / state.sigma)
+        .collect();
+
+    let mean_step = steps
+        .iter()
+        .zip(&params.weights)
+        .fold(DVector::zeros(num_dims), |acc, (step, weight)| {
+            acc + step * *weight
+        });
+
+    state.mean = &old_mean + &mean_step * state.sigma;
+
+    state.p_sigma = &state.p_sigma * (1.0 - params.c_sigma)
+        + state.inv_sqrt_cov()
+            * &mean_step
+            * (params.c_sigma * (2.0 - params.c_sigma) * params.mu_eff).sqrt();
+
+    let p_sigma_norm = state.p_sigma.norm();
+    let h_sigma = p_sigma_norm
+        / (1.0 - (1.0 - params.c_sigma).powi(2 * (generation as i32 + 1))).sqrt()
+        / params.chi_n
+        < 1.4 + 2.0 / (num_dims as f64 + 1.0);
+    let h_sigma = if h_sigma { 1.0 } else { 0.0 };
+
+    state.p_c = &state.p_c * (1.0 - params.c_c)
+        + &mean_step * (h_sigma * (params.c_c * (2.0 - params.c_c) * params.mu_eff).sqrt());
+
+    let rank_mu_update = steps
+        .iter()
+        .zip(&params.weights)
+        .fold(DMatrix::zeros(num_dims, num_dims), |acc, (step, weight)| {
+            acc + step * step.transpose() * *weight
+        });
+
+    state.cov = &state.cov * (1.0 - params.c_1 - params.c_mu)
+        + (&state.p_c * state.p_c.transpose()
+            + &state.cov * ((1.0 - h_sigma) * params.c_c * (2.0 - params.c_c)))
+            * params.c_1
+        + rank_mu_update * params.c_mu;
+
+    state.sigma *= ((params.c_sigma / params.d_sigma) * (p_sigma_norm / params.chi_n - 1.0)).exp();
+
+    state.update_eigen_decomposition();
+}
@@ -0,0 +1,242 @@
+use super::island_model::replace_worst;
+use super::{random_unit_cube_point, snap, sort_key, ProcessingContext};
+use crate::algo::{DifferentialEvolutionConf, DifferentialEvolutionStrategy, Scheduling};
+use crate::param::ParamsSpec;
+use futures::stream::{FuturesUnordered, StreamExt};
+use log::debug;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde_json::Value::Object;
+
+struct Member {
+    point: Vec<f64>,
+    obj_func_val: Option<f64>,
+}
+
+/// Population members live in the unit cube spanned by the spec bounds. Trial vectors are
+/// snapped to the lattice of the spec before evaluation, i.e. integers are rounded and booleans
+/// are flipped once a trial coordinate crosses 0.5, so the population only ever contains
+/// candidates that have actually been evaluated. Under steady-state scheduling, each target is
+/// challenged by a new trial as soon as the evaluation of its previous trial completes.
+pub(super) async fn run(
+    ctx: &ProcessingContext,
+    spec: ParamsSpec,
+    algo_conf: DifferentialEvolutionConf,
+) {
+    let initial_guess = spec.extract_initial_guess();
+
+    debug!("Starting with initial guess: {:?}", &initial_guess);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let population_size = algo_conf.degree_of_par.max(1);
+
+    let initial_points: Vec<Vec<f64>> = (0..population_size)
+        .map(|member_number| {
+            if let Some(imported_candidate) = ctx.imported_candidate(member_number) {
+                spec.to_unit_cube(imported_candidate)
+            } else if member_number == 0 {
+                spec.to_unit_cube(&initial_guess)
+            } else {
+                snap(&spec, &random_unit_cube_point(&spec, &mut rng))
+            }
+        })
+        .collect();
+
+    match algo_conf.scheduling {
+        Scheduling::Generational => {
+            let obj_func_vals = ctx.evaluate(to_candidates(&spec, &initial_points)).await;
+
+            let mut population: Vec<Member> = initial_points
+                .into_iter()
+                .zip(obj_func_vals)
+                .map(|(point, obj_func_val)| Member {
+                    point,
+                    obj_func_val,
+                })
+                .collect();
+
+            for generation in 1.. {
+                take_in_migrants(ctx, &spec, &mut population);
+
+                let trial_points: Vec<Vec<f64>> = (0..population_size)
+                    .map(|target_index| {
+                        create_trial(&population, target_index, &spec, &algo_conf, &mut rng)
+                    })
+                    .collect();
+
+                let obj_func_vals = ctx.evaluate(to_candidates(&spec, &trial_points)).await;
+
+                for ((member, trial_point), obj_func_val) in
+                    population.iter_mut().zip(trial_points).zip(obj_func_vals)
+                {
+                    member.select(trial_point, obj_func_val);
+                }
+
+                debug!("Generation {} completed. Seen: {:?}", generation, ctx.seen);
+            }
+        }
+        Scheduling::SteadyState => {
+            // Members start out unevaluated, such that the evaluation of their initial point is
+            // selected like any trial
+            let mut population: Vec<Member> = initial_points
+                .iter()
+                .map(|point| Member {
+                    point: point.clone(),
+                    obj_func_val: None,
+                })
+                .collect();
+
+            let mut in_flight = FuturesUnordered::new();
+            for (target_index, point) in initial_points.into_iter().enumerate() {
+                let candidate = Object(spec.from_unit_cube(&point));
+                in_flight.push(ctx.evaluate_tagged((target_index, point), candidate));
+            }
+
+            while let Some(((target_index, trial_point), obj_func_val)) = in_flight.next().await {
+                population[target_index].select(trial_point, obj_func_val);
+                debug!("Evaluation completed. Seen: {:?}", ctx.seen);
+
+                take_in_migrants(ctx, &spec, &mut population);
+
+                let trial_point =
+                    create_trial(&population, target_index, &spec, &algo_conf, &mut rng);
+                let candidate = Object(spec.from_unit_cube(&trial_point));
+                in_flight.push(ctx.evaluate_tagged((target_index, trial_point), candidate));
+            }
+        }
+    }
+}
+
+impl Member {
+    fn select(&mut self, trial_point: Vec<f64>, obj_func_val: Option<f64>) {
+        if sort_key(obj_func_val) <= sort_key(self.obj_func_val) {
+            self.point = trial_point;
+            self.obj_func_val = obj_func_val;
+        }
+    }
+}
+
+/// Migrants of the island model replace the worst members, if better
+fn take_in_migrants(ctx: &ProcessingContext, spec: &ParamsSpec, population: &mut [Member]) {
+    replace_worst(
+        population,
+        ctx.migrants(),
+        |member| member.obj_func_val,
+        |member, migrant| {
+            member.point = spec.to_unit_cube(&migrant.candidate);
+            member.obj_func_val = migrant.obj_func_val;
+        },
+    );
+}
+
+fn create_trial(
+    population: &[Member],
+    target_index: usize,
+    spec: &ParamsSpec,
+    algo_conf: &DifferentialEvolutionConf,
+    rng: &mut StdRng,
+) -> Vec<f64> {
+    let best_index = (0..population.len())
+        .min_by(|&a, &b| {
+            sort_key(population[a].obj_func_val)
+                .partial_cmp(&sort_key(population[b].obj_func_val))
+                .unwrap()
+        })
+        .unwrap();
+
+    let donor = create_donor(population, target_index, best_index, algo_conf, rng);
+    let trial = crossover(
+        &population[target_index].point,
+        &donor,
+        algo_conf.crossover_prob,
+        rng,
+    );
+    snap(spec, &trial)
+}
+
+fn create_donor(
+    population: &[Member],
+    target_index: usize,
+    best_index: usize,
+    algo_conf: &DifferentialEvolutionConf,
+    rng: &mut StdRng,
+) -> Vec<f64> {
+    let f = algo_conf.differential_weight;
+    let others = pick_others(population.len(), target_index, 3, rng);
+    let point = |index: usize| &population[index].point;
+
+    match algo_conf.strategy {
+        DifferentialEvolutionStrategy::Rand1Bin => {
+            combine(point(others[0]), &[(f, point(others[1]), point(others[2]))])
+        }
+        DifferentialEvolutionStrategy::Best1Bin => combine(
+            point(best_index),
+            &[(f, point(others[0]), point(others[1]))],
+        ),
+        DifferentialEvolutionStrategy::CurrentToBest1Bin => combine(
+            point(target_index),
+            &[
+                (f, point(best_index), point(target_index)),
+                (f, point(others[0]), point(others[1])),
+            ],
+        ),
+    }
+}
+
+/// Computes base + sum of weight * (a - b) over the given difference terms.
+fn combine(base: &[f64], differences: &[(f64, &Vec<f64>, &Vec<f64>)]) -> Vec<f64> {
+    base.iter()
+        .enumerate()
+        .map(|(dim_index, base_coord)| {
+            base_coord
+                + differences
+                    .iter()
+                    .map(|(weight, a, b)| weight * (a[dim_index] - b[dim_index]))
+                    .sum::<f64>()
+        })
+        .collect()
+}
+
+/// Binomial crossover. At least one coordinate is always taken from the donor.
+fn crossover(target: &[f64], donor: &[f64], crossover_prob: f64, rng: &mut StdRng) -> Vec<f64> {
+    let forced_dim_index = rng.gen_range(0..target.len().max(1));
+    target
+        .iter()
+        .zip(donor)
+        .enumerate()
+        .map(|(dim_index, (target_coord, donor_coord))| {
+            if dim_index == forced_dim_index || rng.gen::<f64>() < crossover_prob {
+                *donor_coord
+            } else {
+                *target_coord
+            }
+        })
+        .collect()
+}
+
+/// Picks `count` population indices other than `exclude_index`, distinct if the population is
+/// large enough.
+fn pick_others(
+    population_size: usize,
+    exclude_index: usize,
+    count: usize,
+    rng: &mut StdRng,
+) -> Vec<usize> {
+    let others: Vec<usize> = (0..population_size)
+        .filter(|&index| index != exclude_index)
+        .collect();
+
+    if others.len() >= count {
+        others.choose_multiple(rng, count).cloned().collect()
+    } else {
+        (0..count)
+            .map(|_| *others.choose(rng).unwrap_or(&exclude_index))
+            .collect()
+    }
+}
+
+fn to_candidates(spec: &ParamsSpec, points: &[Vec<f64>]) -> Vec<serde_json::Value> {
+    points
+        .iter()
+        .map(|point| Object(spec.from_unit_cube(point)))
+        .collect()
+}
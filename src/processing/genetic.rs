@@ -0,0 +1,287 @@
+use super::island_model::replace_worst;
+use super::{create_candidate, random_candidate, sort_key, ProcessingContext};
+use crate::algo::{CrossoverConf, DiscreteCrossover, GeneticConf, RealCrossover, Scheduling};
+use crate::param::{Dim, ParamsSpec, ParamsValue};
+use futures::stream::{FuturesUnordered, StreamExt};
+use log::debug;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde_json::Number as NumberValue;
+use serde_json::Value::{Number, Object};
+
+struct Individual {
+    genome: ParamsValue,
+    obj_func_val: Option<f64>,
+}
+
+/// Under generational scheduling, each generation breeds `degree_of_par` offspring which are all
+/// evaluated in parallel. The elites of the previous generation then replace the worst offspring.
+/// Under steady-state scheduling, a single child is bred whenever an evaluation completes, and an
+/// evaluated child replaces the worst individual unless it is worse, which retains the elites
+/// implicitly.
+pub(super) async fn run(ctx: &ProcessingContext, spec: ParamsSpec, algo_conf: GeneticConf) {
+    let initial_guess = spec.extract_initial_guess();
+
+    debug!("Starting with initial guess: {:?}", &initial_guess);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let population_size = algo_conf.degree_of_par.max(1);
+
+    let initial_genomes: Vec<ParamsValue> = (0..population_size)
+        .map(|individual_number| {
+            if let Some(imported_candidate) = ctx.imported_candidate(individual_number) {
+                imported_candidate.clone()
+            } else if individual_number == 0 {
+                initial_guess.clone()
+            } else {
+                random_candidate(&spec, &mut rng)
+            }
+        })
+        .collect();
+
+    match algo_conf.scheduling {
+        Scheduling::Generational => {
+            let mut population = evaluate(ctx, initial_genomes).await;
+
+            for generation in 1.. {
+                take_in_migrants(ctx, &mut population);
+                sort_by_fitness(&mut population);
+
+                let mut offspring: Vec<ParamsValue> = Vec::with_capacity(population_size + 1);
+                while offspring.len() < population_size {
+                    offspring.extend(breed(&population, &spec, &algo_conf, &mut rng));
+                }
+                offspring.truncate(population_size);
+
+                let mut next_population = evaluate(ctx, offspring).await;
+                sort_by_fitness(&mut next_population);
+
+                let num_elites = algo_conf.num_elites.min(population_size);
+                next_population.truncate(population_size - num_elites);
+                next_population.extend(population.drain(..num_elites));
+                population = next_population;
+
+                debug!("Generation {} completed. Seen: {:?}", generation, ctx.seen);
+            }
+        }
+        Scheduling::SteadyState => {
+            let mut population: Vec<Individual> = Vec::with_capacity(population_size);
+            let mut unborn: Vec<ParamsValue> = Vec::new();
+
+            let mut in_flight = FuturesUnordered::new();
+            for genome in initial_genomes {
+                in_flight.push(ctx.evaluate_tagged(genome.clone(), Object(genome)));
+            }
+
+            while let Some((genome, obj_func_val)) = in_flight.next().await {
+                let individual = Individual {
+                    genome,
+                    obj_func_val,
+                };
+
+                if population.len() < population_size {
+                    population.push(individual);
+                } else {
+                    sort_by_fitness(&mut population);
+                    let worst = population.last_mut().unwrap();
+                    if sort_key(individual.obj_func_val) <= sort_key(worst.obj_func_val) {
+                        *worst = individual;
+                    }
+                }
+                debug!("Evaluation completed. Seen: {:?}", ctx.seen);
+
+                take_in_migrants(ctx, &mut population);
+
+                if unborn.is_empty() {
+                    unborn.extend(breed(&population, &spec, &algo_conf, &mut rng));
+                }
+                let child = unborn.pop().unwrap();
+                in_flight.push(ctx.evaluate_tagged(child.clone(), Object(child)));
+            }
+        }
+    }
+}
+
+/// Migrants of the island model replace the worst individuals, if better
+fn take_in_migrants(ctx: &ProcessingContext, population: &mut [Individual]) {
+    replace_worst(
+        population,
+        ctx.migrants(),
+        |individual| individual.obj_func_val,
+        |individual, migrant| {
+            individual.genome = migrant.candidate;
+            individual.obj_func_val = migrant.obj_func_val;
+        },
+    );
+}
+
+/// Selects two parents, recombines and mutates them
+fn breed(
+    population: &[Individual],
+    spec: &ParamsSpec,
+    algo_conf: &GeneticConf,
+    rng: &mut StdRng,
+) -> [ParamsValue; 2] {
+    let parent_a = select(population, algo_conf.tournament_size, rng);
+    let parent_b = select(population, algo_conf.tournament_size, rng);
+
+    let (child_a, child_b) = if rng.gen::<f64>() < algo_conf.crossover_prob {
+        crossover(
+            &parent_a.genome,
+            &parent_b.genome,
+            spec,
+            &algo_conf.crossover,
+            rng,
+        )
+    } else {
+        (parent_a.genome.clone(), parent_b.genome.clone())
+    };
+
+    [child_a, child_b].map(|child| create_candidate(&child, spec, algo_conf.relative_std_dev, rng))
+}
+
+async fn evaluate(ctx: &ProcessingContext, genomes: Vec<ParamsValue>) -> Vec<Individual> {
+    let candidates = genomes.iter().cloned().map(Object).collect();
+    let obj_func_vals = ctx.evaluate(candidates).await;
+
+    genomes
+        .into_iter()
+        .zip(obj_func_vals)
+        .map(|(genome, obj_func_val)| Individual {
+            genome,
+            obj_func_val,
+        })
+        .collect()
+}
+
+fn sort_by_fitness(population: &mut [Individual]) {
+    population.sort_by(|a, b| {
+        sort_key(a.obj_func_val)
+            .partial_cmp(&sort_key(b.obj_func_val))
+            .unwrap()
+    });
+}
+
+fn select<'a>(
+    population: &'a [Individual],
+    tournament_size: usize,
+    rng: &mut StdRng,
+) -> &'a Individual {
+    (0..tournament_size.max(1))
+        .map(|_| &population[rng.gen_range(0..population.len())])
+        .min_by(|a, b| {
+            sort_key(a.obj_func_val)
+                .partial_cmp(&sort_key(b.obj_func_val))
+                .unwrap()
+        })
+        .unwrap()
+}
+
+pub(super) fn crossover(
+    parent_a: &ParamsValue,
+    parent_b: &ParamsValue,
+    spec: &ParamsSpec,
+    conf: &CrossoverConf,
+    rng: &mut StdRng,
+) -> (ParamsValue, ParamsValue) {
+    let mut child_a = parent_a.clone();
+    let mut child_b = parent_b.clone();
+
+    let bool_names: Vec<&String> = spec
+        .dims
+        .iter()
+        .filter_map(|dim| match dim {
+            Dim::Boolean(dim_spec) => Some(&dim_spec.name),
+            _ => None,
+        })
+        .collect();
+    let int_names: Vec<&String> = spec
+        .dims
+        .iter()
+        .filter_map(|dim| match dim {
+            Dim::Integer(dim_spec_with_bounds) => Some(&dim_spec_with_bounds.dim_spec.name),
+            _ => None,
+        })
+        .collect();
+
+    discrete_crossover(&bool_names, &conf.boolean, &mut child_a, &mut child_b, rng);
+    discrete_crossover(&int_names, &conf.integer, &mut child_a, &mut child_b, rng);
+
+    for dim in &spec.dims {
+        if let Dim::RealNumber(real_num_spec) = dim {
+            let name = &real_num_spec.dim_spec.name;
+            match conf.real {
+                RealCrossover::Uniform => {
+                    if rng.gen::<bool>() {
+                        swap_gene(name, &mut child_a, &mut child_b);
+                    }
+                }
+                RealCrossover::Sbx { distribution_index } => {
+                    let value_a = parent_a.get(name).unwrap().as_f64().unwrap();
+                    let value_b = parent_b.get(name).unwrap().as_f64().unwrap();
+                    let (value_a, value_b) =
+                        simulated_binary_crossover(value_a, value_b, distribution_index, rng);
+                    for (child, value) in [(&mut child_a, value_a), (&mut child_b, value_b)] {
+                        let value = value
+                            .min(real_num_spec.max_value_excl)
+                            .max(real_num_spec.min_value_incl);
+                        child.insert(name.clone(), Number(NumberValue::from_f64(value).unwrap()));
+                    }
+                }
+            }
+        }
+    }
+
+    (child_a, child_b)
+}
+
+fn discrete_crossover(
+    names: &[&String],
+    crossover: &DiscreteCrossover,
+    child_a: &mut ParamsValue,
+    child_b: &mut ParamsValue,
+    rng: &mut StdRng,
+) {
+    match crossover {
+        DiscreteCrossover::Uniform => {
+            for name in names {
+                if rng.gen::<bool>() {
+                    swap_gene(name, child_a, child_b);
+                }
+            }
+        }
+        DiscreteCrossover::OnePoint => {
+            if names.len() > 1 {
+                let cut = rng.gen_range(1..names.len());
+                for name in &names[cut..] {
+                    swap_gene(name, child_a, child_b);
+                }
+            }
+        }
+    }
+}
+
+fn swap_gene(name: &str, child_a: &mut ParamsValue, child_b: &mut ParamsValue) {
+    let value_a = child_a.remove(name).unwrap();
+    let value_b = child_b.insert(name.to_string(), value_a).unwrap();
+    child_a.insert(name.to_string(), value_b);
+}
+
+/// Simulated binary crossover (SBX) after Deb and Agrawal
+fn simulated_binary_crossover(
+    value_a: f64,
+    value_b: f64,
+    distribution_index: f64,
+    rng: &mut StdRng,
+) -> (f64, f64) {
+    let u: f64 = rng.gen();
+    let beta = if u <= 0.5 {
+        (2.0 * u).powf(1.0 / (distribution_index + 1.0))
+    } else {
+        (1.0 / (2.0 * (1.0 - u))).powf(1.0 / (distribution_index + 1.0))
+    };
+
+    (
+        0.5 * ((1.0 + beta) * value_a + (1.0 - beta) * value_b),
+        0.5 * ((1.0 - beta) * value_a + (1.0 + beta) * value_b),
+    )
+}
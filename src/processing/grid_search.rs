@@ -0,0 +1,175 @@
+use super::ProcessingContext;
+use crate::algo::GridSearchConf;
+use crate::param::{Dim, ParamsSpec, ParamsValue};
+use futures::stream::{self, StreamExt};
+use log::debug;
+use serde_json::Number as NumberValue;
+use serde_json::Value::{self, Bool, Number, Object};
+
+/// Grid points are enumerated lazily in mixed-radix order, the last dim varying fastest.
+pub(super) async fn run(ctx: &ProcessingContext, spec: ParamsSpec, algo_conf: GridSearchConf) {
+    let grid: Vec<(&String, Vec<Value>)> = spec
+        .dims
+        .iter()
+        .map(|dim| dim_values(dim, algo_conf.relative_resolution))
+        .collect();
+
+    let num_points = grid
+        .iter()
+        .fold(1usize, |acc, (_, values)| acc.saturating_mul(values.len()));
+    debug!("Grid of {} points", num_points);
+
+    let candidates = (0..num_points).map(|point_index| Object(grid_point(&grid, point_index)));
+
+    stream::iter(candidates)
+        .map(|candidate| ctx.evaluate_one(candidate))
+        .buffer_unordered(algo_conf.degree_of_par.max(1))
+        .for_each(|_| async {})
+        .await;
+
+    debug!("Grid exhausted. Seen: {:?}", ctx.seen);
+}
+
+fn grid_point(grid: &[(&String, Vec<Value>)], point_index: usize) -> ParamsValue {
+    let mut result = ParamsValue::new();
+    let mut remainder = point_index;
+
+    for (name, values) in grid.iter().rev() {
+        result.insert(name.to_string(), values[remainder % values.len()].clone());
+        remainder /= values.len();
+    }
+
+    result
+}
+
+fn dim_values(dim: &Dim, relative_resolution: f64) -> (&String, Vec<Value>) {
+    match dim {
+        Dim::Boolean(dim_spec) => (&dim_spec.name, vec![Bool(false), Bool(true)]),
+        Dim::RealNumber(dim_spec_with_bounds) => {
+            let min_value = dim_spec_with_bounds.min_value_incl;
+            let max_value = dim_spec_with_bounds.max_value_excl;
+            let range = max_value - min_value;
+            let num_values = if range > 0.0 {
+                ((1.0 / relative_resolution) + 1e-9).floor() as usize + 1
+            } else {
+                1
+            };
+            let values = (0..num_values)
+                .map(|step_num| {
+                    let value = (min_value + step_num as f64 * relative_resolution * range)
+                        .min(max_value.max(min_value));
+                    Number(NumberValue::from_f64(value).unwrap())
+                })
+                .collect();
+            (&dim_spec_with_bounds.dim_spec.name, values)
+        }
+        Dim::Integer(dim_spec_with_bounds) => {
+            let min_value = dim_spec_with_bounds.min_value_incl;
+            let max_value = dim_spec_with_bounds.max_value_excl.max(min_value);
+            let step =
+                ((relative_resolution * (max_value - min_value) as f64).round() as usize).max(1);
+            let values = (min_value..=max_value)
+                .step_by(step)
+                .map(|value| Number(NumberValue::from(value)))
+                .collect();
+            (&dim_spec_with_bounds.dim_spec.name, values)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::param::{DimSpec, DimSpecWithBounds};
+    use serde_json::json;
+
+    fn real_dim(min_value: f64, max_value: f64) -> Dim {
+        Dim::RealNumber(DimSpecWithBounds::new(
+            "x".to_string(),
+            min_value,
+            min_value,
+            max_value,
+        ))
+    }
+
+    fn integer_dim(min_value: i64, max_value: i64) -> Dim {
+        Dim::Integer(DimSpecWithBounds::new(
+            "n".to_string(),
+            min_value,
+            min_value,
+            max_value,
+        ))
+    }
+
+    #[test]
+    fn real_values_step_from_the_lower_bound_up_to_the_upper_bound() {
+        let dim = real_dim(-10.0, 10.0);
+
+        assert_eq!(
+            dim_values(&dim, 0.25).1,
+            vec![
+                json!(-10.0),
+                json!(-5.0),
+                json!(0.0),
+                json!(5.0),
+                json!(10.0)
+            ]
+        );
+        assert_eq!(dim_values(&dim, 0.05).1.len(), 21);
+        assert_eq!(
+            dim_values(&dim, 0.4).1,
+            vec![json!(-10.0), json!(-2.0), json!(6.0)]
+        );
+        assert_eq!(dim_values(&dim, 1.0).1, vec![json!(-10.0), json!(10.0)]);
+        assert_eq!(dim_values(&real_dim(1.0, 1.0), 0.25).1, vec![json!(1.0)]);
+    }
+
+    #[test]
+    fn integer_steps_are_rounded_to_at_least_one() {
+        assert_eq!(
+            dim_values(&integer_dim(0, 10), 0.25).1,
+            vec![json!(0), json!(3), json!(6), json!(9)]
+        );
+        assert_eq!(
+            dim_values(&integer_dim(0, 10), 0.5).1,
+            vec![json!(0), json!(5), json!(10)]
+        );
+        assert_eq!(dim_values(&integer_dim(0, 10), 0.01).1.len(), 11);
+        assert_eq!(dim_values(&integer_dim(5, 5), 0.25).1, vec![json!(5)]);
+    }
+
+    #[test]
+    fn booleans_take_both_values() {
+        let dim = Dim::Boolean(DimSpec {
+            name: "b".to_string(),
+            initial_value: true,
+        });
+
+        assert_eq!(dim_values(&dim, 0.5).1, vec![json!(false), json!(true)]);
+    }
+
+    #[test]
+    fn grid_points_vary_the_last_dim_fastest() {
+        let (a, b) = ("a".to_string(), "b".to_string());
+        let grid = vec![
+            (&a, vec![json!(1), json!(2)]),
+            (&b, vec![json!("x"), json!("y"), json!("z")]),
+        ];
+
+        let points: Vec<Value> = (0..6)
+            .map(|point_index| Object(grid_point(&grid, point_index)))
+            .collect();
+
+        assert_eq!(
+            points,
+            vec![
+                json!({"a": 1, "b": "x"}),
+                json!({"a": 1, "b": "y"}),
+                json!({"a": 1, "b": "z"}),
+                json!({"a": 2, "b": "x"}),
+                json!({"a": 2, "b": "y"}),
+                json!({"a": 2, "b": "z"}),
+            ]
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use super::{random_candidate, sort_key, ProcessingContext};
+use crate::algo::HyperbandConf;
+use crate::param::{ParamsSpec, ParamsValue};
+use log::debug;
+use rand::{rngs::StdRng, SeedableRng};
+use serde_json::Value::Object;
+
+/// Brackets are repeated until processing is stopped, each time with fresh random candidates. The
+/// initial guess is the first candidate of the first bracket.
+pub(super) async fn run(ctx: &ProcessingContext, spec: ParamsSpec, algo_conf: HyperbandConf) {
+    let initial_guess = spec.extract_initial_guess();
+
+    debug!("Starting with initial guess: {:?}", &initial_guess);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let degree_of_par = algo_conf.degree_of_par.max(1);
+    let brackets = brackets(&algo_conf);
+
+    let mut initial_guess = Some(initial_guess);
+
+    for round in 0.. {
+        for &(s, num_candidates) in &brackets {
+            let mut candidates: Vec<ParamsValue> = (0..num_candidates)
+                .map(|_| {
+                    initial_guess
+                        .take()
+                        .unwrap_or_else(|| random_candidate(&spec, &mut rng))
+                })
+                .collect();
+
+            for rung in 0..=s {
+                let budget = rung_budget(&algo_conf, s, rung);
+
+                let mut obj_func_vals = Vec::with_capacity(candidates.len());
+                for batch in candidates.chunks(degree_of_par) {
+                    let batch = batch.iter().cloned().map(Object).collect();
+                    obj_func_vals.extend(ctx.evaluate_with_budget(batch, budget).await);
+                }
+
+                debug!(
+                    "Rung {} of bracket {} in round {} completed with {} candidates at budget {}. Seen: {:?}",
+                    rung,
+                    s,
+                    round,
+                    candidates.len(),
+                    budget,
+                    ctx.seen
+                );
+
+                if rung == s {
+                    break;
+                }
+
+                let num_promoted = num_promoted(candidates.len(), algo_conf.reduction_factor);
+                let mut ranked: Vec<(ParamsValue, Option<f64>)> =
+                    candidates.into_iter().zip(obj_func_vals).collect();
+                ranked.sort_by(|a, b| sort_key(a.1).partial_cmp(&sort_key(b.1)).unwrap());
+                candidates = ranked
+                    .into_iter()
+                    .take(num_promoted)
+                    .map(|(candidate, _)| candidate)
+                    .collect();
+            }
+        }
+    }
+}
+
+/// The brackets of a round, most exploratory first, as the index `s` of each bracket along with
+/// the number of candidates it starts with. Bracket `s` has `s + 1` rungs.
+fn brackets(algo_conf: &HyperbandConf) -> Vec<(usize, usize)> {
+    let eta = algo_conf.reduction_factor;
+
+    let s_max = ((algo_conf.max_budget / algo_conf.min_budget).ln() / eta.ln())
+        .floor()
+        .max(0.0) as usize;
+    let num_brackets = algo_conf
+        .num_brackets
+        .unwrap_or(s_max + 1)
+        .clamp(1, s_max + 1);
+
+    (s_max + 1 - num_brackets..=s_max)
+        .rev()
+        .map(|s| {
+            let num_candidates =
+                ((s_max + 1) as f64 / (s + 1) as f64 * eta.powi(s as i32)).ceil() as usize;
+            (s, num_candidates)
+        })
+        .collect()
+}
+
+/// The budget of a rung of bracket `s`, which reaches the max budget at its last rung
+fn rung_budget(algo_conf: &HyperbandConf, s: usize, rung: usize) -> f64 {
+    algo_conf.max_budget * algo_conf.reduction_factor.powi(rung as i32 - s as i32)
+}
+
+/// The number of candidates promoted to the next rung, at least one
+fn num_promoted(num_candidates: usize, reduction_factor: f64) -> usize {
+    ((num_candidates as f64 / reduction_factor).floor() as usize).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn algo_conf(num_brackets: Option<usize>) -> HyperbandConf {
+        HyperbandConf {
+            min_budget: 1.0,
+            max_budget: 81.0,
+            reduction_factor: 3.0,
+            degree_of_par: 1,
+            num_brackets,
+        }
+    }
+
+    #[test]
+    fn brackets_follow_the_hyperband_paper() {
+        assert_eq!(
+            brackets(&algo_conf(None)),
+            vec![(4, 81), (3, 34), (2, 15), (1, 8), (0, 5)]
+        );
+    }
+
+    #[test]
+    fn num_brackets_keeps_the_most_exploratory_ones() {
+        assert_eq!(brackets(&algo_conf(Some(2))), vec![(4, 81), (3, 34)]);
+        assert_eq!(brackets(&algo_conf(Some(0))), vec![(4, 81)]);
+        assert_eq!(brackets(&algo_conf(Some(9))).len(), 5);
+    }
+
+    #[test]
+    fn equal_budgets_give_a_single_bracket_of_one_rung() {
+        let algo_conf = HyperbandConf {
+            min_budget: 5.0,
+            max_budget: 5.0,
+            ..algo_conf(None)
+        };
+
+        assert_eq!(brackets(&algo_conf), vec![(0, 1)]);
+        assert_eq!(rung_budget(&algo_conf, 0, 0), 5.0);
+    }
+
+    #[test]
+    fn rung_budgets_grow_to_the_max_budget() {
+        let algo_conf = algo_conf(None);
+        let budgets: Vec<f64> = (0..=4)
+            .map(|rung| rung_budget(&algo_conf, 4, rung))
+            .collect();
+
+        for (budget, expected) in budgets.iter().zip([1.0, 3.0, 9.0, 27.0, 81.0]) {
+            assert!((budget - expected).abs() < 1e-9);
+        }
+        assert!((rung_budget(&algo_conf, 1, 0) - 27.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn at_least_one_candidate_is_promoted() {
+        assert_eq!(num_promoted(81, 3.0), 27);
+        assert_eq!(num_promoted(34, 3.0), 11);
+        assert_eq!(num_promoted(2, 3.0), 1);
+    }
+}
@@ -0,0 +1,173 @@
+use super::{sort_key, Evaluation, ProcessingContext};
+use crate::algo::{AlgoConf, IslandModelConf};
+use crate::param::ParamsSpec;
+use futures::future;
+use log::debug;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// The evaluations of an island, along with the migrants its algorithm has yet to take in. The
+/// best of both is the island's own best, which its algorithm follows instead of the best of all
+/// islands, such that islands only learn from each other by migration.
+pub(super) struct Island {
+    pub number: usize,
+    evaluations: Mutex<Vec<Evaluation>>,
+    migrants: Mutex<Vec<Evaluation>>,
+    best: Mutex<Option<Evaluation>>,
+    notify: Notify,
+}
+
+impl Island {
+    fn new(number: usize) -> Island {
+        Island {
+            number,
+            evaluations: Mutex::new(Vec::new()),
+            migrants: Mutex::new(Vec::new()),
+            best: Mutex::new(None),
+            notify: Notify::new(),
+        }
+    }
+
+    pub fn record(&self, evaluation: Evaluation) {
+        self.update_best(&evaluation);
+        self.evaluations.lock().unwrap().push(evaluation);
+        self.notify.notify_one();
+    }
+
+    fn receive(&self, migrants: Vec<Evaluation>) {
+        for migrant in &migrants {
+            self.update_best(migrant);
+        }
+        self.migrants.lock().unwrap().extend(migrants);
+    }
+
+    fn update_best(&self, evaluation: &Evaluation) {
+        let mut best = self.best.lock().unwrap();
+        if evaluation.obj_func_val.is_some()
+            && best
+                .as_ref()
+                .map(|best| sort_key(evaluation.obj_func_val) < sort_key(best.obj_func_val))
+                .unwrap_or(true)
+        {
+            *best = Some(evaluation.clone());
+        }
+    }
+
+    /// The best candidate of the island's evaluations and received migrants, with its value to be
+    /// minimised
+    pub fn best_evaluation(&self) -> Option<Evaluation> {
+        self.best.lock().unwrap().clone()
+    }
+
+    /// The migrants received since the previous call, best first
+    pub fn take_migrants(&self) -> Vec<Evaluation> {
+        let mut migrants = std::mem::take(&mut *self.migrants.lock().unwrap());
+        super::sort_evaluations(&mut migrants);
+        migrants
+    }
+
+    async fn num_evaluations_reached(&self, num_evaluations: usize) {
+        while self.evaluations.lock().unwrap().len() < num_evaluations {
+            self.notify.notified().await;
+        }
+    }
+
+    fn best(&self, num_evaluations: usize) -> Vec<Evaluation> {
+        let mut evaluations = self.evaluations.lock().unwrap().clone();
+        super::sort_evaluations(&mut evaluations);
+        evaluations
+            .into_iter()
+            .filter(|evaluation| evaluation.obj_func_val.is_some())
+            .take(num_evaluations)
+            .collect()
+    }
+}
+
+/// Lets each migrant that is better than the worst member of a population replace it, where
+/// `obj_func_val` gives the value of a member
+pub(super) fn replace_worst<T>(
+    population: &mut [T],
+    migrants: Vec<Evaluation>,
+    obj_func_val: impl Fn(&T) -> Option<f64>,
+    mut replace: impl FnMut(&mut T, Evaluation),
+) {
+    for migrant in migrants {
+        let worst = population.iter_mut().max_by(|a, b| {
+            sort_key(obj_func_val(a))
+                .partial_cmp(&sort_key(obj_func_val(b)))
+                .unwrap()
+        });
+        match worst {
+            Some(worst) if sort_key(migrant.obj_func_val) < sort_key(obj_func_val(worst)) => {
+                replace(worst, migrant);
+            }
+            _ => break,
+        }
+    }
+}
+
+pub(super) async fn run(ctx: &ProcessingContext, spec: ParamsSpec, algo_conf: IslandModelConf) {
+    let num_islands = algo_conf.islands.len();
+    let islands: Vec<Arc<Island>> = (0..num_islands)
+        .map(|number| Arc::new(Island::new(number)))
+        .collect();
+
+    let island_runs = algo_conf
+        .islands
+        .iter()
+        .enumerate()
+        .map(|(number, island_algo_conf)| {
+            let mut island_algo_conf = island_algo_conf.clone();
+            *island_algo_conf.degree_of_par_mut() = algo_conf.degree_of_par / num_islands
+                + usize::from(number < algo_conf.degree_of_par % num_islands);
+
+            run_island(
+                ctx,
+                &spec,
+                island_algo_conf,
+                &islands[number],
+                &islands[(number + num_islands - 1) % num_islands],
+                &algo_conf,
+            )
+        });
+
+    future::join_all(island_runs).await;
+
+    debug!("All islands exhausted. Seen: {:?}", ctx.seen);
+}
+
+async fn run_island(
+    ctx: &ProcessingContext,
+    spec: &ParamsSpec,
+    island_algo_conf: AlgoConf,
+    island: &Arc<Island>,
+    previous_island: &Island,
+    algo_conf: &IslandModelConf,
+) {
+    let migration_interval = algo_conf.migration_interval.max(1);
+    let island_ctx = ctx.for_island(island.clone());
+
+    let migrations = async {
+        for epoch in 1.. {
+            island
+                .num_evaluations_reached(epoch * migration_interval)
+                .await;
+
+            let migrants = previous_island.best(algo_conf.num_migrants);
+            debug!(
+                "Migrating {} candidates from island {} to island {}",
+                migrants.len(),
+                previous_island.number,
+                island.number
+            );
+            island.receive(migrants);
+        }
+    };
+
+    tokio::select! {
+        _ = Box::pin(super::run(&island_ctx, spec.clone(), island_algo_conf)) => {}
+        _ = migrations => {}
+    }
+
+    debug!("Island {} exhausted", island.number);
+}
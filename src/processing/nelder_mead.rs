@@ -0,0 +1,241 @@
+use super::{sort_key, ProcessingContext};
+use crate::algo::NelderMeadConf;
+use crate::param::ParamsSpec;
+use log::debug;
+use serde_json::Value::Object;
+
+const REFLECTION: f64 = 1.0;
+const EXPANSION: f64 = 2.0;
+const CONTRACTION: f64 = 0.5;
+const SHRINKAGE: f64 = 0.5;
+
+const REFLECTED: usize = 0;
+const EXPANDED: usize = 1;
+const OUTSIDE_CONTRACTED: usize = 2;
+const INSIDE_CONTRACTED: usize = 3;
+
+struct Vertex {
+    point: Vec<f64>,
+    obj_func_val: Option<f64>,
+}
+
+/// Trial points of one iteration, of which up to `degree_of_par` are evaluated at once, in the
+/// order in which the Nelder-Mead decision logic may ask for them.
+struct Speculation {
+    points: Vec<Vec<f64>>,
+    obj_func_vals: Vec<Option<Option<f64>>>,
+}
+
+impl Speculation {
+    fn new(points: Vec<Vec<f64>>) -> Speculation {
+        let num_points = points.len();
+        Speculation {
+            points,
+            obj_func_vals: vec![None; num_points],
+        }
+    }
+
+    async fn obj_func_val(
+        &mut self,
+        index: usize,
+        ctx: &ProcessingContext,
+        spec: &ParamsSpec,
+        degree_of_par: usize,
+    ) -> Option<f64> {
+        if self.obj_func_vals[index].is_none() {
+            let indices: Vec<usize> = (index..self.points.len())
+                .filter(|&i| self.obj_func_vals[i].is_none())
+                .take(degree_of_par.max(1))
+                .collect();
+            let candidates = indices
+                .iter()
+                .map(|&i| Object(spec.from_unit_cube(&self.points[i])))
+                .collect();
+            let obj_func_vals = ctx.evaluate(candidates).await;
+            for (i, obj_func_val) in indices.into_iter().zip(obj_func_vals) {
+                self.obj_func_vals[i] = Some(obj_func_val);
+            }
+        }
+        self.obj_func_vals[index].unwrap()
+    }
+
+    fn take_vertex(&mut self, index: usize) -> Vertex {
+        Vertex {
+            point: std::mem::take(&mut self.points[index]),
+            obj_func_val: self.obj_func_vals[index].unwrap(),
+        }
+    }
+}
+
+/// Nelder-Mead in the unit cube spanned by the spec bounds. All points are clamped to the bounds.
+pub(super) async fn run(ctx: &ProcessingContext, spec: ParamsSpec, algo_conf: NelderMeadConf) {
+    let initial_guess = spec.extract_initial_guess();
+
+    debug!("Starting with initial guess: {:?}", &initial_guess);
+
+    let degree_of_par = algo_conf.degree_of_par;
+    let initial_point = spec.to_unit_cube(&initial_guess);
+    let num_dims = initial_point.len();
+
+    let initial_points: Vec<Vec<f64>> = (0..=num_dims)
+        .map(|vertex_number| {
+            let mut point = initial_point.clone();
+            if vertex_number > 0 {
+                let coord = &mut point[vertex_number - 1];
+                *coord = if *coord + algo_conf.initial_simplex_scale <= 1.0 {
+                    *coord + algo_conf.initial_simplex_scale
+                } else {
+                    *coord - algo_conf.initial_simplex_scale
+                };
+            }
+            point
+        })
+        .collect();
+
+    let obj_func_vals = evaluate_in_batches(ctx, &spec, &initial_points, degree_of_par).await;
+    let mut simplex: Vec<Vertex> = initial_points
+        .into_iter()
+        .zip(obj_func_vals)
+        .map(|(point, obj_func_val)| Vertex {
+            point,
+            obj_func_val,
+        })
+        .collect();
+
+    for iter_num in 0.. {
+        simplex.sort_by(|a, b| {
+            sort_key(a.obj_func_val)
+                .partial_cmp(&sort_key(b.obj_func_val))
+                .unwrap()
+        });
+
+        let best_val = sort_key(simplex[0].obj_func_val);
+        let second_worst_val = sort_key(simplex[num_dims.saturating_sub(1)].obj_func_val);
+        let worst_val = sort_key(simplex[num_dims].obj_func_val);
+
+        let centroid = compute_centroid(&simplex[..num_dims]);
+        let worst_point = &simplex[num_dims].point;
+        let along = |coeff: f64| -> Vec<f64> {
+            centroid
+                .iter()
+                .zip(worst_point)
+                .map(|(c, w)| (c + coeff * (c - w)).clamp(0.0, 1.0))
+                .collect()
+        };
+
+        let mut speculation = Speculation::new(vec![
+            along(REFLECTION),
+            along(REFLECTION * EXPANSION),
+            along(REFLECTION * CONTRACTION),
+            along(-CONTRACTION),
+        ]);
+
+        let reflected_val = sort_key(
+            speculation
+                .obj_func_val(REFLECTED, ctx, &spec, degree_of_par)
+                .await,
+        );
+
+        let replacement = if reflected_val < best_val {
+            let expanded_val = sort_key(
+                speculation
+                    .obj_func_val(EXPANDED, ctx, &spec, degree_of_par)
+                    .await,
+            );
+            if expanded_val < reflected_val {
+                Some(EXPANDED)
+            } else {
+                Some(REFLECTED)
+            }
+        } else if reflected_val < second_worst_val {
+            Some(REFLECTED)
+        } else if reflected_val < worst_val {
+            let contracted_val = sort_key(
+                speculation
+                    .obj_func_val(OUTSIDE_CONTRACTED, ctx, &spec, degree_of_par)
+                    .await,
+            );
+            (contracted_val <= reflected_val).then_some(OUTSIDE_CONTRACTED)
+        } else {
+            let contracted_val = sort_key(
+                speculation
+                    .obj_func_val(INSIDE_CONTRACTED, ctx, &spec, degree_of_par)
+                    .await,
+            );
+            (contracted_val < worst_val).then_some(INSIDE_CONTRACTED)
+        };
+
+        match replacement {
+            Some(index) => {
+                simplex[num_dims] = speculation.take_vertex(index);
+            }
+            None => {
+                shrink(ctx, &spec, &mut simplex, degree_of_par).await;
+            }
+        }
+
+        debug!("Iteration {} completed. Seen: {:?}", iter_num, ctx.seen);
+    }
+}
+
+async fn shrink(
+    ctx: &ProcessingContext,
+    spec: &ParamsSpec,
+    simplex: &mut [Vertex],
+    degree_of_par: usize,
+) {
+    let best_point = simplex[0].point.clone();
+    let shrunk_points: Vec<Vec<f64>> = simplex[1..]
+        .iter()
+        .map(|vertex| {
+            best_point
+                .iter()
+                .zip(&vertex.point)
+                .map(|(b, v)| b + SHRINKAGE * (v - b))
+                .collect()
+        })
+        .collect();
+
+    let obj_func_vals = evaluate_in_batches(ctx, spec, &shrunk_points, degree_of_par).await;
+    for ((vertex, point), obj_func_val) in simplex[1..]
+        .iter_mut()
+        .zip(shrunk_points)
+        .zip(obj_func_vals)
+    {
+        vertex.point = point;
+        vertex.obj_func_val = obj_func_val;
+    }
+}
+
+async fn evaluate_in_batches(
+    ctx: &ProcessingContext,
+    spec: &ParamsSpec,
+    points: &[Vec<f64>],
+    degree_of_par: usize,
+) -> Vec<Option<f64>> {
+    let mut obj_func_vals = Vec::with_capacity(points.len());
+    for batch in points.chunks(degree_of_par.max(1)) {
+        let candidates = batch
+            .iter()
+            .map(|point| Object(spec.from_unit_cube(point)))
+            .collect();
+        obj_func_vals.extend(ctx.evaluate(candidates).await);
+    }
+    obj_func_vals
+}
+
+fn compute_centroid(vertices: &[Vertex]) -> Vec<f64> {
+    let num_dims = vertices
+        .first()
+        .map(|vertex| vertex.point.len())
+        .unwrap_or(0);
+    (0..num_dims)
+        .map(|dim_index| {
+            vertices
+                .iter()
+                .map(|vertex| vertex.point[dim_index])
+                .sum::<f64>()
+                / vertices.len() as f64
+        })
+        .collect()
+}
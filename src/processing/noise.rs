@@ -0,0 +1,236 @@
+use super::evaluation_key;
+use crate::domain::{NoiseHandlingConf, ObjectiveDirection};
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+
+/// Running mean and variance of the objective function values of a candidate, after Welford
+#[derive(Debug, Default)]
+pub(super) struct SampleStats {
+    pub num_samples: usize,
+    pub mean: f64,
+    sum_of_squared_deviations: f64,
+}
+
+impl SampleStats {
+    fn add(&mut self, obj_func_val: f64) {
+        self.num_samples += 1;
+        let deviation = obj_func_val - self.mean;
+        self.mean += deviation / self.num_samples as f64;
+        self.sum_of_squared_deviations += deviation * (obj_func_val - self.mean);
+    }
+
+    /// Half width of the confidence interval of the mean, given the quantile of the standard
+    /// normal distribution. Undefined for less than two samples.
+    pub fn confidence_half_width(&self, z: f64) -> Option<f64> {
+        if self.num_samples < 2 {
+            return None;
+        }
+        let variance = self.sum_of_squared_deviations / (self.num_samples - 1) as f64;
+        Some(z * (variance / self.num_samples as f64).sqrt())
+    }
+}
+
+/// Mean to be minimised, totally ordered
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RankedMean(f64);
+
+impl Eq for RankedMean {}
+
+impl PartialOrd for RankedMean {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RankedMean {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// A candidate sampled at a budget, if any
+#[derive(Debug)]
+struct Entry {
+    candidate: serde_json::Value,
+    budget: Option<f64>,
+    stats: SampleStats,
+}
+
+/// Sample statistics of all candidates of a processing job, separately for each budget. The
+/// incumbent is the candidate with the best mean among those sampled at least `min_samples`
+/// times, which are ranked as they are sampled.
+#[derive(Debug)]
+pub(super) struct Samples {
+    pub conf: NoiseHandlingConf,
+    direction: ObjectiveDirection,
+    entries: HashMap<String, Entry>,
+    ranking: BTreeSet<(RankedMean, String)>,
+    num_evaluations: usize,
+}
+
+impl Samples {
+    pub fn new(conf: NoiseHandlingConf, direction: ObjectiveDirection) -> Samples {
+        Samples {
+            conf,
+            direction,
+            entries: HashMap::new(),
+            ranking: BTreeSet::new(),
+            num_evaluations: 0,
+        }
+    }
+
+    pub fn add(
+        &mut self,
+        candidate: &serde_json::Value,
+        budget: Option<f64>,
+        obj_func_val: f64,
+    ) -> &SampleStats {
+        let key = evaluation_key(candidate, budget);
+        let entry = self.entries.entry(key.clone()).or_insert_with(|| Entry {
+            candidate: candidate.clone(),
+            budget,
+            stats: SampleStats::default(),
+        });
+
+        if entry.stats.num_samples >= self.conf.min_samples {
+            self.ranking.remove(&(
+                RankedMean(self.direction.to_minimized(entry.stats.mean)),
+                key.clone(),
+            ));
+        }
+        entry.stats.add(obj_func_val);
+        if entry.stats.num_samples >= self.conf.min_samples {
+            self.ranking.insert((
+                RankedMean(self.direction.to_minimized(entry.stats.mean)),
+                key,
+            ));
+        }
+
+        &entry.stats
+    }
+
+    pub fn get(&self, candidate: &serde_json::Value, budget: Option<f64>) -> Option<&SampleStats> {
+        self.entries
+            .get(&evaluation_key(candidate, budget))
+            .map(|entry| &entry.stats)
+    }
+
+    /// The incumbent with the budget it was sampled at and its mean
+    pub fn incumbent(&self) -> Option<(&serde_json::Value, Option<f64>, f64)> {
+        self.ranking.first().map(|(_, key)| {
+            let entry = &self.entries[key];
+            (&entry.candidate, entry.budget, entry.stats.mean)
+        })
+    }
+
+    /// A candidate is a promising challenger while it lacks samples to become the incumbent,
+    /// but its mean beats the incumbent's.
+    pub fn is_promising_challenger(
+        &self,
+        candidate: &serde_json::Value,
+        budget: Option<f64>,
+    ) -> bool {
+        match self.get(candidate, budget) {
+            Some(stats) if stats.num_samples < self.conf.min_samples => self
+                .incumbent()
+                .map(|(_, _, incumbent_mean)| self.direction.is_better(stats.mean, incumbent_mean))
+                .unwrap_or(true),
+            _ => false,
+        }
+    }
+
+    /// Counts an evaluation and returns whether the incumbent is due for re-evaluation, never if
+    /// the re-evaluation interval is 0
+    pub fn on_evaluation(&mut self) -> bool {
+        self.num_evaluations += 1;
+        self.num_evaluations
+            .is_multiple_of(self.conf.reevaluation_interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn samples(min_samples: usize, direction: ObjectiveDirection) -> Samples {
+        Samples::new(
+            NoiseHandlingConf {
+                min_samples,
+                reevaluation_interval: 0,
+                confidence_z: 1.0,
+            },
+            direction,
+        )
+    }
+
+    #[test]
+    fn sample_stats_track_mean_and_variance() {
+        let mut stats = SampleStats::default();
+        for obj_func_val in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            stats.add(obj_func_val);
+        }
+
+        assert_eq!(stats.num_samples, 8);
+        assert!((stats.mean - 5.0).abs() < 1e-12);
+        // Sample variance 32 / 7
+        let expected_half_width = 2.0 * (32.0 / 7.0 / 8.0_f64).sqrt();
+        assert!((stats.confidence_half_width(2.0).unwrap() - expected_half_width).abs() < 1e-12);
+    }
+
+    #[test]
+    fn confidence_half_width_needs_two_samples() {
+        let mut stats = SampleStats::default();
+        assert!(stats.confidence_half_width(1.0).is_none());
+
+        stats.add(1.0);
+        assert!(stats.confidence_half_width(1.0).is_none());
+
+        stats.add(1.0);
+        assert_eq!(stats.confidence_half_width(1.0), Some(0.0));
+    }
+
+    #[test]
+    fn incumbent_is_best_mean_with_enough_samples() {
+        let mut samples = samples(2, ObjectiveDirection::Minimize);
+        let (a, b) = (json!({"x": 1}), json!({"x": 2}));
+
+        samples.add(&a, None, 1.0);
+        assert!(samples.incumbent().is_none());
+        assert!(samples.is_promising_challenger(&a, None));
+
+        samples.add(&a, None, 3.0);
+        samples.add(&b, None, 0.0);
+        assert_eq!(samples.incumbent(), Some((&a, None, 2.0)));
+        assert!(samples.is_promising_challenger(&b, None));
+
+        samples.add(&b, None, 6.0);
+        assert_eq!(samples.incumbent(), Some((&a, None, 2.0)));
+        assert!(!samples.is_promising_challenger(&b, None));
+    }
+
+    #[test]
+    fn incumbent_follows_the_objective_direction() {
+        let mut samples = samples(1, ObjectiveDirection::Maximize);
+        let (a, b) = (json!({"x": 1}), json!({"x": 2}));
+
+        samples.add(&a, None, 1.0);
+        samples.add(&b, None, 2.0);
+
+        assert_eq!(samples.incumbent(), Some((&b, None, 2.0)));
+    }
+
+    #[test]
+    fn samples_at_different_budgets_are_kept_apart() {
+        let mut samples = samples(1, ObjectiveDirection::Minimize);
+        let a = json!({"x": 1});
+
+        samples.add(&a, Some(1.0), 5.0);
+        samples.add(&a, Some(3.0), 1.0);
+
+        assert_eq!(samples.get(&a, Some(1.0)).unwrap().mean, 5.0);
+        assert_eq!(samples.get(&a, Some(3.0)).unwrap().mean, 1.0);
+        assert!(samples.get(&a, None).is_none());
+        assert_eq!(samples.incumbent(), Some((&a, Some(3.0), 1.0)));
+    }
+}
@@ -0,0 +1,188 @@
+use super::genetic::crossover;
+use super::{create_candidate, random_candidate, ProcessingContext};
+use crate::algo::NsgaIIConf;
+use crate::param::{ParamsSpec, ParamsValue};
+use crate::pareto;
+use log::debug;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde_json::Value::Object;
+use std::cmp::Ordering;
+
+struct Individual {
+    genome: ParamsValue,
+    obj_func_vals: Option<Vec<f64>>,
+}
+
+#[derive(Clone, Copy)]
+struct Fitness {
+    rank: usize,
+    crowding_distance: f64,
+}
+
+/// Each generation breeds `degree_of_par` offspring, which are evaluated in parallel. Parents and
+/// offspring are then ranked into non-dominated fronts together, and the next population is
+/// filled front by front, breaking ties within the last front by crowding distance. Candidates
+/// whose evaluation failed rank behind all fronts.
+pub(super) async fn run(ctx: &ProcessingContext, spec: ParamsSpec, algo_conf: NsgaIIConf) {
+    let initial_guess = spec.extract_initial_guess();
+
+    debug!("Starting with initial guess: {:?}", &initial_guess);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let population_size = algo_conf.degree_of_par.max(1);
+
+    let initial_genomes: Vec<ParamsValue> = (0..population_size)
+        .map(|individual_number| {
+            if let Some(imported_candidate) = ctx.imported_candidate(individual_number) {
+                imported_candidate.clone()
+            } else if individual_number == 0 {
+                initial_guess.clone()
+            } else {
+                random_candidate(&spec, &mut rng)
+            }
+        })
+        .collect();
+
+    let mut population = evaluate(ctx, initial_genomes).await;
+
+    for generation in 1.. {
+        let fitness = assign_fitness(&population);
+
+        let mut offspring: Vec<ParamsValue> = Vec::with_capacity(population_size + 1);
+        while offspring.len() < population_size {
+            let parent_a = &population[select(&fitness, &mut rng)];
+            let parent_b = &population[select(&fitness, &mut rng)];
+
+            let (child_a, child_b) = if rng.gen::<f64>() < algo_conf.crossover_prob {
+                crossover(
+                    &parent_a.genome,
+                    &parent_b.genome,
+                    &spec,
+                    &algo_conf.crossover,
+                    &mut rng,
+                )
+            } else {
+                (parent_a.genome.clone(), parent_b.genome.clone())
+            };
+
+            for child in [child_a, child_b] {
+                offspring.push(create_candidate(
+                    &child,
+                    &spec,
+                    algo_conf.relative_std_dev,
+                    &mut rng,
+                ));
+            }
+        }
+        // Migrants of the island model take the place of offspring, as their objective function
+        // values are evaluated along with them
+        let migrants = ctx.migrants();
+        offspring.truncate(population_size.saturating_sub(migrants.len()));
+        offspring.extend(
+            migrants
+                .into_iter()
+                .take(population_size)
+                .map(|migrant| migrant.candidate),
+        );
+
+        population.extend(evaluate(ctx, offspring).await);
+
+        let fitness = assign_fitness(&population);
+        let mut order: Vec<usize> = (0..population.len()).collect();
+        order.sort_by(|&a, &b| compare(&fitness[a], &fitness[b]));
+        order.truncate(population_size);
+        order.sort_unstable();
+
+        population = population
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| order.binary_search(index).is_ok())
+            .map(|(_, individual)| individual)
+            .collect();
+
+        debug!(
+            "Generation {} completed with {} individuals in the first front",
+            generation,
+            fitness.iter().filter(|fitness| fitness.rank == 0).count()
+        );
+    }
+}
+
+async fn evaluate(ctx: &ProcessingContext, genomes: Vec<ParamsValue>) -> Vec<Individual> {
+    let candidates = genomes.iter().cloned().map(Object).collect();
+    let obj_func_vals = ctx.evaluate_multi(candidates).await;
+
+    genomes
+        .into_iter()
+        .zip(obj_func_vals)
+        .map(|(genome, obj_func_vals)| Individual {
+            genome,
+            obj_func_vals,
+        })
+        .collect()
+}
+
+/// Non-dominated sorting by repeatedly peeling off the front of the remaining individuals, with
+/// crowding distances computed per front.
+fn assign_fitness(population: &[Individual]) -> Vec<Fitness> {
+    let mut fitness = vec![
+        Fitness {
+            rank: 0,
+            crowding_distance: 0.0,
+        };
+        population.len()
+    ];
+
+    let mut remaining: Vec<usize> = (0..population.len())
+        .filter(|&index| population[index].obj_func_vals.is_some())
+        .collect();
+    let vals = |index: usize| population[index].obj_func_vals.as_deref().unwrap();
+
+    let mut rank = 0;
+    while !remaining.is_empty() {
+        let (front, rest): (Vec<usize>, Vec<usize>) = remaining.iter().partition(|&&index| {
+            !remaining
+                .iter()
+                .any(|&other| pareto::dominates(vals(other), vals(index)))
+        });
+
+        let points: Vec<&[f64]> = front.iter().map(|&index| vals(index)).collect();
+        for (&index, crowding_distance) in front.iter().zip(pareto::crowding_distances(&points)) {
+            fitness[index] = Fitness {
+                rank,
+                crowding_distance,
+            };
+        }
+
+        remaining = rest;
+        rank += 1;
+    }
+
+    for (individual, fitness) in population.iter().zip(fitness.iter_mut()) {
+        if individual.obj_func_vals.is_none() {
+            fitness.rank = rank;
+        }
+    }
+
+    fitness
+}
+
+/// Lower rank first, then larger crowding distance first
+fn compare(a: &Fitness, b: &Fitness) -> Ordering {
+    a.rank.cmp(&b.rank).then(
+        b.crowding_distance
+            .partial_cmp(&a.crowding_distance)
+            .unwrap(),
+    )
+}
+
+/// Binary tournament
+fn select(fitness: &[Fitness], rng: &mut StdRng) -> usize {
+    let a = rng.gen_range(0..fitness.len());
+    let b = rng.gen_range(0..fitness.len());
+    if compare(&fitness[b], &fitness[a]) == Ordering::Less {
+        b
+    } else {
+        a
+    }
+}
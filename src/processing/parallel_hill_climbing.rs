@@ -0,0 +1,432 @@
+use super::{create_candidate, random_candidate, ProcessingContext};
+use crate::algo::{AlgoConf, ParallelHillClimbingConf, Scheduling, StepSizeAdaptationConf};
+use crate::param::ParamsSpec;
+use futures::stream::{FuturesUnordered, StreamExt};
+use log::debug;
+use rand::{rngs::StdRng, SeedableRng};
+use serde_json::Value::Object;
+
+/// Success rate based adaptation of the relative standard deviation. A trial is successful if it
+/// improves on the center objective function value at the time its candidate was created.
+struct StepSize {
+    relative_std_dev: f64,
+    adaptation: Option<StepSizeAdaptationConf>,
+    num_trials: usize,
+    num_successes: usize,
+}
+
+impl StepSize {
+    fn new(algo_conf: &ParallelHillClimbingConf) -> StepSize {
+        StepSize {
+            relative_std_dev: algo_conf.relative_std_dev,
+            adaptation: algo_conf.step_size_adaptation.clone(),
+            num_trials: 0,
+            num_successes: 0,
+        }
+    }
+
+    /// Returns whether the step size has changed
+    fn on_trial(
+        &mut self,
+        best_obj_func_val_before: Option<f64>,
+        obj_func_val: Option<f64>,
+    ) -> bool {
+        let adaptation = match (&self.adaptation, best_obj_func_val_before) {
+            (Some(adaptation), Some(_)) => adaptation,
+            _ => return false,
+        };
+
+        self.num_trials += 1;
+        if let (Some(best_before), Some(obj_func_val)) = (best_obj_func_val_before, obj_func_val) {
+            if obj_func_val < best_before {
+                self.num_successes += 1;
+            }
+        }
+
+        if self.num_trials < adaptation.window_size.max(1) {
+            return false;
+        }
+
+        let success_rate = self.num_successes as f64 / self.num_trials as f64;
+        self.num_trials = 0;
+        self.num_successes = 0;
+
+        if success_rate > adaptation.target_success_rate {
+            self.relative_std_dev *= adaptation.adaptation_factor;
+            true
+        } else if success_rate < adaptation.target_success_rate {
+            self.relative_std_dev /= adaptation.adaptation_factor;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Tag of an evaluation, relating its result to the search it was created for
+struct Trial {
+    restart_num: usize,
+    candidate: serde_json::Value,
+    center_obj_func_val_before: Option<f64>,
+}
+
+/// Local search state. Candidates are created around the center, which is the best candidate
+/// since the last restart. Without restarts, the center is the best seen candidate of the job.
+struct HillClimber<'a> {
+    ctx: &'a ProcessingContext,
+    spec: &'a ParamsSpec,
+    algo_conf: ParallelHillClimbingConf,
+    step_size: StepSize,
+    restart_num: usize,
+    population_size: usize,
+    center: serde_json::Value,
+    center_obj_func_val: Option<f64>,
+    center_submitted: bool,
+    evals_without_improvement: usize,
+}
+
+impl<'a> HillClimber<'a> {
+    fn new(
+        ctx: &'a ProcessingContext,
+        spec: &'a ParamsSpec,
+        algo_conf: ParallelHillClimbingConf,
+    ) -> HillClimber<'a> {
+        let step_size = StepSize::new(&algo_conf);
+        ctx.report_step_size(step_size.relative_std_dev);
+
+        HillClimber {
+            ctx,
+            spec,
+            step_size,
+            restart_num: 0,
+            population_size: algo_conf.degree_of_par.max(1),
+            algo_conf,
+            center: Object(spec.extract_initial_guess()),
+            center_obj_func_val: None,
+            center_submitted: false,
+            evals_without_improvement: 0,
+        }
+    }
+
+    fn degree_of_par(&self) -> usize {
+        self.algo_conf.degree_of_par.max(1)
+    }
+
+    /// Applies the latest algo conf update, if any, without losing the search state. A changed
+    /// relative standard deviation replaces the current, possibly adapted, one. A changed degree of
+    /// parallelism also becomes the population size, unless grown by restarts. The scheduling
+    /// cannot change while running.
+    fn apply_algo_conf_update(&mut self) {
+        match self.ctx.algo_conf_update() {
+            Some(AlgoConf::ParallelHillClimbing(mut algo_conf)) => {
+                algo_conf.scheduling = self.algo_conf.scheduling;
+
+                if algo_conf.relative_std_dev != self.algo_conf.relative_std_dev {
+                    self.step_size.relative_std_dev = algo_conf.relative_std_dev;
+                    self.ctx.report_step_size(self.step_size.relative_std_dev);
+                }
+                self.step_size.adaptation = algo_conf.step_size_adaptation.clone();
+
+                if self.population_size == self.degree_of_par() {
+                    self.population_size = algo_conf.degree_of_par.max(1);
+                }
+
+                debug!("Applying algo conf update: {:?}", algo_conf);
+                self.algo_conf = algo_conf;
+            }
+            Some(algo_conf) => {
+                debug!("Ignoring algo conf update of another algo: {:?}", algo_conf);
+            }
+            None => return,
+        }
+
+        self.ctx
+            .report_effective_algo_conf(AlgoConf::ParallelHillClimbing(self.algo_conf.clone()));
+    }
+
+    /// The first candidate after a (re)start is the center itself
+    fn next_trial(&mut self, rng: &mut StdRng) -> Trial {
+        let candidate = if self.center_submitted {
+            Object(create_candidate(
+                self.center.as_object().unwrap(),
+                self.spec,
+                self.step_size.relative_std_dev,
+                rng,
+            ))
+        } else {
+            self.center_submitted = true;
+            self.center.clone()
+        };
+
+        Trial {
+            restart_num: self.restart_num,
+            candidate,
+            center_obj_func_val_before: self.center_obj_func_val,
+        }
+    }
+
+    /// Results of trials created before the latest restart only count towards the best seen
+    /// state of the job, which the processing context maintains.
+    fn on_result(&mut self, trial: Trial, obj_func_val: Option<f64>, rng: &mut StdRng) {
+        if trial.restart_num != self.restart_num {
+            return;
+        }
+
+        if self
+            .step_size
+            .on_trial(trial.center_obj_func_val_before, obj_func_val)
+        {
+            self.ctx.report_step_size(self.step_size.relative_std_dev);
+        }
+
+        let improved = match (obj_func_val, self.center_obj_func_val) {
+            (Some(obj_func_val), Some(center_obj_func_val)) => obj_func_val < center_obj_func_val,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+
+        if improved {
+            self.center = trial.candidate;
+            self.center_obj_func_val = obj_func_val;
+            self.evals_without_improvement = 0;
+        } else {
+            self.evals_without_improvement += 1;
+        }
+
+        if let Some(restart_conf) = &self.algo_conf.restart {
+            if self.evals_without_improvement >= restart_conf.evaluations_without_improvement {
+                self.restart(restart_conf.population_growth_factor, rng);
+            }
+        }
+    }
+
+    fn restart(&mut self, population_growth_factor: f64, rng: &mut StdRng) {
+        self.restart_num += 1;
+        if self.algo_conf.scheduling == Scheduling::Generational {
+            self.population_size =
+                ((self.population_size as f64 * population_growth_factor).round() as usize).max(1);
+        }
+        self.center = Object(random_candidate(self.spec, rng));
+        self.center_obj_func_val = None;
+        self.center_submitted = false;
+        self.evals_without_improvement = 0;
+        self.step_size = StepSize::new(&self.algo_conf);
+
+        debug!(
+            "Restart {} with population size {} from {:?}",
+            self.restart_num, self.population_size, self.center
+        );
+        self.ctx
+            .report_restart(self.restart_num, self.population_size);
+        self.ctx.report_step_size(self.step_size.relative_std_dev);
+    }
+}
+
+/// Under generational scheduling, each iteration creates `population_size` candidates around the
+/// center, evaluated in batches of `degree_of_par`. The population size only ever grows through
+/// restarts with a population growth factor above 1. Algo conf updates apply from the next
+/// iteration, or from the next candidate under steady-state scheduling.
+pub(super) async fn run(
+    ctx: &ProcessingContext,
+    spec: ParamsSpec,
+    algo_conf: ParallelHillClimbingConf,
+) {
+    debug!(
+        "Starting with initial guess: {:?}",
+        spec.extract_initial_guess()
+    );
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let mut hill_climber = HillClimber::new(ctx, &spec, algo_conf);
+
+    match hill_climber.algo_conf.scheduling {
+        Scheduling::Generational => {
+            for iter_num in 0.. {
+                hill_climber.apply_algo_conf_update();
+                let degree_of_par = hill_climber.degree_of_par();
+
+                let trials: Vec<Trial> = (0..hill_climber.population_size)
+                    .map(|_| hill_climber.next_trial(&mut rng))
+                    .collect();
+
+                let mut obj_func_vals = Vec::with_capacity(trials.len());
+                for batch in trials.chunks(degree_of_par) {
+                    let candidates = batch.iter().map(|trial| trial.candidate.clone()).collect();
+                    obj_func_vals.extend(ctx.evaluate(candidates).await);
+                }
+
+                for (trial, obj_func_val) in trials.into_iter().zip(obj_func_vals) {
+                    hill_climber.on_result(trial, obj_func_val, &mut rng);
+                }
+
+                debug!("Iteration {} completed. Seen: {:?}", iter_num, ctx.seen);
+            }
+        }
+        Scheduling::SteadyState => {
+            let mut in_flight = FuturesUnordered::new();
+
+            loop {
+                hill_climber.apply_algo_conf_update();
+
+                while in_flight.len() < hill_climber.degree_of_par() {
+                    let trial = hill_climber.next_trial(&mut rng);
+                    let candidate = trial.candidate.clone();
+                    in_flight.push(ctx.evaluate_tagged(trial, candidate));
+                }
+
+                if let Some((trial, obj_func_val)) = in_flight.next().await {
+                    hill_climber.on_result(trial, obj_func_val, &mut rng);
+                }
+                debug!("Evaluation completed. Seen: {:?}", ctx.seen);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::GridSearchConf;
+    use crate::app_state::AppEvent;
+    use crate::domain::{ObjectiveConf, StatusMessage, TerminationConf};
+    use crate::obj_func::ObjFuncCallDef;
+    use crate::type_aliases::{AlgoConfSender, EventReceiver};
+    use serde_json::json;
+    use std::sync::Mutex;
+    use std::time::SystemTime;
+    use tokio::sync::{mpsc, watch};
+
+    fn algo_conf() -> ParallelHillClimbingConf {
+        ParallelHillClimbingConf {
+            relative_std_dev: 0.1,
+            degree_of_par: 2,
+            scheduling: Scheduling::Generational,
+            step_size_adaptation: None,
+            restart: None,
+        }
+    }
+
+    fn spec() -> ParamsSpec {
+        ParamsSpec::from_json(json!({
+            "definition": {"x": [-10, 10]},
+            "initial_guess": {"x": 0.0},
+        }))
+        .unwrap()
+    }
+
+    fn ctx(spec: &ParamsSpec) -> (ProcessingContext, AlgoConfSender, EventReceiver) {
+        let (event_sender, event_receiver) = mpsc::unbounded_channel();
+        let (algo_conf_sender, algo_conf_receiver) =
+            watch::channel(AlgoConf::ParallelHillClimbing(algo_conf()));
+
+        let mut ctx = ProcessingContext::new(
+            SystemTime::now(),
+            ObjFuncCallDef {
+                program: String::new(),
+                args: Vec::new(),
+            },
+            TerminationConf::default(),
+            ObjectiveConf::default(),
+            event_sender,
+            spec,
+            Vec::new(),
+        );
+        ctx.algo_conf_updates = Some(Mutex::new(algo_conf_receiver));
+        (ctx, algo_conf_sender, event_receiver)
+    }
+
+    fn effective_algo_confs(event_receiver: &mut EventReceiver) -> Vec<AlgoConf> {
+        let mut algo_confs = Vec::new();
+        while let Ok(event) = event_receiver.try_recv() {
+            if let AppEvent::DelegateStatusMessage(StatusMessage::EffectiveAlgoConf(algo_conf)) =
+                event
+            {
+                algo_confs.push(algo_conf);
+            }
+        }
+        algo_confs
+    }
+
+    #[test]
+    fn updates_apply_except_for_the_scheduling() {
+        let spec = spec();
+        let (ctx, algo_conf_sender, mut event_receiver) = ctx(&spec);
+        let mut hill_climber = HillClimber::new(&ctx, &spec, algo_conf());
+
+        hill_climber.apply_algo_conf_update();
+        assert!(effective_algo_confs(&mut event_receiver).is_empty());
+
+        algo_conf_sender
+            .send(AlgoConf::ParallelHillClimbing(ParallelHillClimbingConf {
+                relative_std_dev: 0.3,
+                degree_of_par: 4,
+                scheduling: Scheduling::SteadyState,
+                ..algo_conf()
+            }))
+            .unwrap();
+        hill_climber.apply_algo_conf_update();
+
+        assert_eq!(hill_climber.step_size.relative_std_dev, 0.3);
+        assert_eq!(hill_climber.population_size, 4);
+        assert_eq!(hill_climber.algo_conf.scheduling, Scheduling::Generational);
+        match effective_algo_confs(&mut event_receiver).as_slice() {
+            [AlgoConf::ParallelHillClimbing(effective_algo_conf)] => {
+                assert_eq!(effective_algo_conf.degree_of_par, 4);
+                assert_eq!(effective_algo_conf.scheduling, Scheduling::Generational);
+            }
+            algo_confs => panic!("Expected the effective algo conf, got {:?}", algo_confs),
+        }
+    }
+
+    #[test]
+    fn updates_keep_the_search_state() {
+        let spec = spec();
+        let (ctx, algo_conf_sender, mut event_receiver) = ctx(&spec);
+        let mut hill_climber = HillClimber::new(&ctx, &spec, algo_conf());
+        hill_climber.center = json!({"x": 5.0});
+        hill_climber.center_obj_func_val = Some(1.0);
+        hill_climber.step_size.relative_std_dev = 0.05;
+        hill_climber.population_size = 6;
+
+        // An unchanged relative standard deviation keeps the adapted one
+        algo_conf_sender
+            .send(AlgoConf::ParallelHillClimbing(ParallelHillClimbingConf {
+                degree_of_par: 3,
+                ..algo_conf()
+            }))
+            .unwrap();
+        hill_climber.apply_algo_conf_update();
+
+        assert_eq!(hill_climber.center, json!({"x": 5.0}));
+        assert_eq!(hill_climber.center_obj_func_val, Some(1.0));
+        assert_eq!(hill_climber.step_size.relative_std_dev, 0.05);
+        // Population grown by restarts
+        assert_eq!(hill_climber.population_size, 6);
+        assert_eq!(hill_climber.degree_of_par(), 3);
+        assert_eq!(effective_algo_confs(&mut event_receiver).len(), 1);
+    }
+
+    #[test]
+    fn updates_of_another_algo_are_ignored() {
+        let spec = spec();
+        let (ctx, algo_conf_sender, mut event_receiver) = ctx(&spec);
+        let mut hill_climber = HillClimber::new(&ctx, &spec, algo_conf());
+
+        algo_conf_sender
+            .send(AlgoConf::GridSearch(GridSearchConf {
+                relative_resolution: 0.5,
+                degree_of_par: 8,
+            }))
+            .unwrap();
+        hill_climber.apply_algo_conf_update();
+
+        assert_eq!(hill_climber.degree_of_par(), 2);
+        assert_eq!(hill_climber.step_size.relative_std_dev, 0.1);
+        match effective_algo_confs(&mut event_receiver).as_slice() {
+            [AlgoConf::ParallelHillClimbing(effective_algo_conf)] => {
+                assert_eq!(effective_algo_conf.degree_of_par, 2);
+            }
+            algo_confs => panic!("Expected the unchanged algo conf, got {:?}", algo_confs),
+        }
+    }
+}
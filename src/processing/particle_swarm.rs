@@ -0,0 +1,170 @@
+use super::island_model::replace_worst;
+use super::{random_unit_cube_point, ProcessingContext};
+use crate::algo::{ParticleSwarmConf, Scheduling};
+use crate::param::ParamsSpec;
+use futures::stream::{FuturesUnordered, StreamExt};
+use log::debug;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde_json::Value::Object;
+
+struct Particle {
+    position: Vec<f64>,
+    velocity: Vec<f64>,
+    best_position: Vec<f64>,
+    best_obj_func_val: Option<f64>,
+}
+
+/// Particles move in the unit cube spanned by the spec bounds, hence velocities are clamped to
+/// `max_relative_velocity` times the range of each dim. The global best is the best seen
+/// candidate of the processing context. Under steady-state scheduling, each particle moves on as
+/// soon as its own evaluation completes.
+pub(super) async fn run(ctx: &ProcessingContext, spec: ParamsSpec, algo_conf: ParticleSwarmConf) {
+    let initial_guess = spec.extract_initial_guess();
+
+    debug!("Starting with initial guess: {:?}", &initial_guess);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let num_dims = spec.dims.len();
+    let max_velocity = algo_conf.max_relative_velocity;
+    let degree_of_par = algo_conf.degree_of_par.max(1);
+
+    let mut swarm: Vec<Particle> = (0..degree_of_par)
+        .map(|particle_number| {
+            let position = if let Some(imported_candidate) = ctx.imported_candidate(particle_number)
+            {
+                spec.to_unit_cube(imported_candidate)
+            } else if particle_number == 0 {
+                spec.to_unit_cube(&initial_guess)
+            } else {
+                random_unit_cube_point(&spec, &mut rng)
+            };
+            let velocity = (0..num_dims)
+                .map(|_| rng.gen_range(-max_velocity..=max_velocity))
+                .collect();
+            Particle {
+                best_position: position.clone(),
+                position,
+                velocity,
+                best_obj_func_val: None,
+            }
+        })
+        .collect();
+
+    let global_best_position = || {
+        ctx.best_candidate()
+            .map(|candidate| spec.to_unit_cube(candidate.as_object().unwrap()))
+    };
+
+    match algo_conf.scheduling {
+        Scheduling::Generational => {
+            for iter_num in 0.. {
+                if iter_num > 0 {
+                    take_in_migrants(ctx, &spec, &mut swarm);
+                    let global_best_position = global_best_position();
+                    for particle in &mut swarm {
+                        move_particle(
+                            particle,
+                            global_best_position.as_deref(),
+                            &algo_conf,
+                            &mut rng,
+                        );
+                    }
+                }
+
+                let candidates = swarm
+                    .iter()
+                    .map(|particle| Object(spec.from_unit_cube(&particle.position)))
+                    .collect();
+
+                let obj_func_vals = ctx.evaluate(candidates).await;
+
+                for (particle, obj_func_val) in swarm.iter_mut().zip(obj_func_vals) {
+                    particle.update_best(obj_func_val);
+                }
+
+                debug!("Iteration {} completed. Seen: {:?}", iter_num, ctx.seen);
+            }
+        }
+        Scheduling::SteadyState => {
+            let mut in_flight = FuturesUnordered::new();
+            for (particle_number, particle) in swarm.iter().enumerate() {
+                let candidate = Object(spec.from_unit_cube(&particle.position));
+                in_flight.push(ctx.evaluate_tagged(particle_number, candidate));
+            }
+
+            while let Some((particle_number, obj_func_val)) = in_flight.next().await {
+                let particle = &mut swarm[particle_number];
+                particle.update_best(obj_func_val);
+                debug!("Evaluation completed. Seen: {:?}", ctx.seen);
+
+                take_in_migrants(ctx, &spec, &mut swarm);
+                let particle = &mut swarm[particle_number];
+                move_particle(
+                    particle,
+                    global_best_position().as_deref(),
+                    &algo_conf,
+                    &mut rng,
+                );
+                let candidate = Object(spec.from_unit_cube(&particle.position));
+                in_flight.push(ctx.evaluate_tagged(particle_number, candidate));
+            }
+        }
+    }
+}
+
+impl Particle {
+    fn update_best(&mut self, obj_func_val: Option<f64>) {
+        if let Some(obj_func_val) = obj_func_val {
+            if self
+                .best_obj_func_val
+                .map(|best_obj_func_val| obj_func_val < best_obj_func_val)
+                .unwrap_or(true)
+            {
+                self.best_position = self.position.clone();
+                self.best_obj_func_val = Some(obj_func_val);
+            }
+        }
+    }
+}
+
+/// Migrants of the island model replace the worst personal bests, if better, which leaves the
+/// positions of the particles and their evaluations in flight untouched
+fn take_in_migrants(ctx: &ProcessingContext, spec: &ParamsSpec, swarm: &mut [Particle]) {
+    replace_worst(
+        swarm,
+        ctx.migrants(),
+        |particle| particle.best_obj_func_val,
+        |particle, migrant| {
+            particle.best_position = spec.to_unit_cube(&migrant.candidate);
+            particle.best_obj_func_val = migrant.obj_func_val;
+        },
+    );
+}
+
+fn move_particle(
+    particle: &mut Particle,
+    global_best_position: Option<&[f64]>,
+    algo_conf: &ParticleSwarmConf,
+    rng: &mut StdRng,
+) {
+    let max_velocity = algo_conf.max_relative_velocity;
+
+    for dim_index in 0..particle.position.len() {
+        let position = particle.position[dim_index];
+        let velocity = algo_conf.inertia * particle.velocity[dim_index]
+            + algo_conf.cognitive_coeff
+                * rng.gen::<f64>()
+                * (particle.best_position[dim_index] - position)
+            + global_best_position
+                .map(|global_best_position| {
+                    algo_conf.social_coeff
+                        * rng.gen::<f64>()
+                        * (global_best_position[dim_index] - position)
+                })
+                .unwrap_or(0.0);
+        let velocity = velocity.clamp(-max_velocity, max_velocity);
+
+        particle.velocity[dim_index] = velocity;
+        particle.position[dim_index] = (position + velocity).clamp(0.0, 1.0);
+    }
+}
@@ -0,0 +1,130 @@
+use super::ProcessingContext;
+use crate::algo::PatternSearchConf;
+use crate::param::{Dim, ParamsSpec, ParamsValue};
+use log::debug;
+use serde_json::Number as NumberValue;
+use serde_json::Value::{Bool, Number, Object};
+
+/// Poll points that already failed around the current center are not evaluated again, so polls
+/// on an integer lattice whose steps no longer shrink only cost evaluations once.
+pub(super) async fn run(ctx: &ProcessingContext, spec: ParamsSpec, algo_conf: PatternSearchConf) {
+    let initial_guess = spec.extract_initial_guess();
+
+    debug!("Starting with initial guess: {:?}", &initial_guess);
+
+    let degree_of_par = algo_conf.degree_of_par.max(1);
+    let mut relative_step = algo_conf.initial_relative_step;
+
+    let mut center_obj_func_val = ctx.evaluate_one(Object(initial_guess.clone())).await;
+    let mut center = initial_guess;
+    let mut failed_poll_points: Vec<ParamsValue> = Vec::new();
+
+    for poll_num in 0.. {
+        let poll_points: Vec<ParamsValue> = poll(&center, &spec, relative_step)
+            .into_iter()
+            .filter(|poll_point| !failed_poll_points.contains(poll_point))
+            .collect();
+
+        // Smaller steps produce no other poll points either, since integer steps are at least 1
+        // and real steps have vanished
+        if poll_points.is_empty() {
+            break;
+        }
+
+        let mut obj_func_vals = Vec::with_capacity(poll_points.len());
+        for batch in poll_points.chunks(degree_of_par) {
+            let candidates = batch.iter().cloned().map(Object).collect();
+            obj_func_vals.extend(ctx.evaluate(candidates).await);
+        }
+
+        let best = poll_points
+            .iter()
+            .zip(&obj_func_vals)
+            .filter_map(|(poll_point, obj_func_val)| obj_func_val.map(|val| (poll_point, val)))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+        match best {
+            Some((poll_point, obj_func_val))
+                if center_obj_func_val
+                    .map(|center_obj_func_val| obj_func_val < center_obj_func_val)
+                    .unwrap_or(true) =>
+            {
+                center = poll_point.clone();
+                center_obj_func_val = Some(obj_func_val);
+                failed_poll_points.clear();
+            }
+            _ => {
+                failed_poll_points.extend(poll_points);
+                relative_step /= 2.0;
+                ctx.report_step_size(relative_step);
+
+                if relative_step < algo_conf.min_relative_step {
+                    break;
+                }
+            }
+        }
+
+        debug!(
+            "Poll {} completed with relative step {}. Seen: {:?}",
+            poll_num, relative_step, ctx.seen
+        );
+    }
+
+    debug!(
+        "Minimum step reached or poll exhausted. Seen: {:?}",
+        ctx.seen
+    );
+}
+
+/// Neighbours of the center one step up and down along each coordinate, within bounds
+fn poll(center: &ParamsValue, spec: &ParamsSpec, relative_step: f64) -> Vec<ParamsValue> {
+    let mut result = Vec::new();
+
+    for dim in &spec.dims {
+        match dim {
+            Dim::Boolean(dim_spec) => {
+                let value = center.get(&dim_spec.name).unwrap().as_bool().unwrap();
+                let mut poll_point = center.clone();
+                poll_point.insert(dim_spec.name.clone(), Bool(!value));
+                result.push(poll_point);
+            }
+            Dim::RealNumber(dim_spec_with_bounds) => {
+                let name = &dim_spec_with_bounds.dim_spec.name;
+                let value = center.get(name).unwrap().as_f64().unwrap();
+                let min_value = dim_spec_with_bounds.min_value_incl;
+                let max_value = dim_spec_with_bounds.max_value_excl;
+                let step = relative_step * (max_value - min_value);
+
+                for new_value in [value - step, value + step] {
+                    let new_value = new_value.clamp(min_value, max_value.max(min_value));
+                    if new_value != value {
+                        let mut poll_point = center.clone();
+                        poll_point.insert(
+                            name.clone(),
+                            Number(NumberValue::from_f64(new_value).unwrap()),
+                        );
+                        result.push(poll_point);
+                    }
+                }
+            }
+            Dim::Integer(dim_spec_with_bounds) => {
+                let name = &dim_spec_with_bounds.dim_spec.name;
+                let value = center.get(name).unwrap().as_i64().unwrap();
+                let min_value = dim_spec_with_bounds.min_value_incl;
+                let max_value = dim_spec_with_bounds.max_value_excl.max(min_value);
+                let step = ((relative_step * (max_value - min_value) as f64).round() as i64).max(1);
+
+                for new_value in [value - step, value + step] {
+                    let new_value = new_value.clamp(min_value, max_value);
+                    if new_value != value {
+                        let mut poll_point = center.clone();
+                        poll_point.insert(name.clone(), Number(NumberValue::from(new_value)));
+                        result.push(poll_point);
+                    }
+                }
+            }
+        }
+    }
+
+    result
+}
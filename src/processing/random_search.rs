@@ -0,0 +1,41 @@
+use super::random_candidate;
+use crate::algo::RandomSearchConf;
+use crate::optimizer::Optimizer;
+use crate::param::{ParamsSpec, ParamsValue};
+use rand::{rngs::StdRng, SeedableRng};
+
+/// Proposes `num_samples` candidates, starting with the initial guess, regardless of results
+pub(super) struct RandomSearch {
+    spec: ParamsSpec,
+    num_remaining: usize,
+    initial_guess: Option<ParamsValue>,
+    rng: StdRng,
+}
+
+impl RandomSearch {
+    pub fn new(spec: ParamsSpec, algo_conf: &RandomSearchConf) -> RandomSearch {
+        RandomSearch {
+            initial_guess: Some(spec.extract_initial_guess()),
+            spec,
+            num_remaining: algo_conf.num_samples,
+            rng: StdRng::seed_from_u64(0),
+        }
+    }
+}
+
+impl Optimizer for RandomSearch {
+    fn ask(&mut self, n: usize) -> Vec<ParamsValue> {
+        let n = n.min(self.num_remaining);
+        self.num_remaining -= n;
+
+        (0..n)
+            .map(|_| {
+                self.initial_guess
+                    .take()
+                    .unwrap_or_else(|| random_candidate(&self.spec, &mut self.rng))
+            })
+            .collect()
+    }
+
+    fn tell(&mut self, _candidate: ParamsValue, _obj_func_val: Option<f64>) {}
+}
@@ -0,0 +1,220 @@
+use super::{create_candidate, ProcessingContext};
+use crate::algo::{CoolingSchedule, Scheduling, SimulatedAnnealingConf};
+use crate::param::ParamsSpec;
+use futures::stream::{FuturesUnordered, StreamExt};
+use log::debug;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde_json::Value::Object;
+
+struct Chain {
+    current_candidate: serde_json::Value,
+    current_obj_func_val: Option<f64>,
+}
+
+impl Chain {
+    fn step(
+        &mut self,
+        candidate: serde_json::Value,
+        obj_func_val: Option<f64>,
+        temperature: f64,
+        rng: &mut StdRng,
+    ) {
+        if accept(self.current_obj_func_val, obj_func_val, temperature, rng) {
+            self.current_candidate = candidate;
+            self.current_obj_func_val = obj_func_val;
+        }
+    }
+}
+
+/// Cooling schedule and reheating state, advanced once per iteration
+struct Schedule<'a> {
+    algo_conf: &'a SimulatedAnnealingConf,
+    start_temperature: f64,
+    iter_num: usize,
+    best_obj_func_val: Option<f64>,
+    iters_without_improvement: usize,
+}
+
+impl<'a> Schedule<'a> {
+    fn new(algo_conf: &'a SimulatedAnnealingConf) -> Schedule<'a> {
+        Schedule {
+            algo_conf,
+            start_temperature: algo_conf.initial_temperature,
+            iter_num: 0,
+            best_obj_func_val: None,
+            iters_without_improvement: 0,
+        }
+    }
+
+    fn temperature(&self) -> f64 {
+        compute_temperature(
+            &self.algo_conf.cooling_schedule,
+            self.start_temperature,
+            self.iter_num,
+        )
+    }
+
+    /// Advances the schedule. Upon reheating, every chain continues from the best seen candidate.
+    fn on_iteration_completed(&mut self, ctx: &ProcessingContext, chains: &mut [Chain]) {
+        self.iter_num += 1;
+
+        let new_best_obj_func_val = ctx.best_obj_func_val();
+        if new_best_obj_func_val != self.best_obj_func_val {
+            self.best_obj_func_val = new_best_obj_func_val;
+            self.iters_without_improvement = 0;
+        } else {
+            self.iters_without_improvement += 1;
+        }
+
+        if let Some(reheating) = &self.algo_conf.reheating {
+            if self.iters_without_improvement >= reheating.iterations_without_improvement {
+                debug!(
+                    "Reheating after {} iterations without improvement",
+                    self.iters_without_improvement
+                );
+                self.start_temperature =
+                    self.algo_conf.initial_temperature * reheating.temperature_fraction;
+                self.iter_num = 0;
+                self.iters_without_improvement = 0;
+
+                if let Some(best_candidate) = ctx.best_candidate() {
+                    for chain in chains {
+                        chain.current_candidate = best_candidate.clone();
+                        chain.current_obj_func_val = self.best_obj_func_val;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Runs `degree_of_par` Metropolis chains. The chains share the best seen state of the processing
+/// context: upon reheating, every chain continues from the best seen candidate. Under steady-state
+/// scheduling, each chain advances as soon as its own evaluation completes, and an iteration of
+/// the schedule corresponds to `degree_of_par` completed evaluations.
+pub(super) async fn run(
+    ctx: &ProcessingContext,
+    spec: ParamsSpec,
+    algo_conf: SimulatedAnnealingConf,
+) {
+    let initial_guess = Object(spec.extract_initial_guess());
+
+    debug!("Starting with initial guess: {:?}", &initial_guess);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let num_chains = algo_conf.degree_of_par.max(1);
+    let mut chains: Vec<Chain> = (0..num_chains)
+        .map(|_| Chain {
+            current_candidate: initial_guess.clone(),
+            current_obj_func_val: None,
+        })
+        .collect();
+
+    let mut schedule = Schedule::new(&algo_conf);
+
+    let propose = |chain: &Chain, rng: &mut StdRng| {
+        Object(create_candidate(
+            chain.current_candidate.as_object().unwrap(),
+            &spec,
+            algo_conf.relative_std_dev,
+            rng,
+        ))
+    };
+
+    match algo_conf.scheduling {
+        Scheduling::Generational => {
+            for iter_num in 0.. {
+                let temperature = schedule.temperature();
+
+                let candidates: Vec<serde_json::Value> = chains
+                    .iter()
+                    .enumerate()
+                    .map(|(chain_number, chain)| {
+                        if iter_num == 0 && chain_number == 0 {
+                            initial_guess.clone()
+                        } else {
+                            propose(chain, &mut rng)
+                        }
+                    })
+                    .collect();
+
+                let obj_func_vals = ctx.evaluate(candidates.clone()).await;
+
+                for ((chain, candidate), obj_func_val) in
+                    chains.iter_mut().zip(candidates).zip(obj_func_vals)
+                {
+                    chain.step(candidate, obj_func_val, temperature, &mut rng);
+                }
+
+                schedule.on_iteration_completed(ctx, &mut chains);
+
+                debug!(
+                    "Iteration {} completed at temperature {}. Seen: {:?}",
+                    iter_num, temperature, ctx.seen
+                );
+            }
+        }
+        Scheduling::SteadyState => {
+            let mut in_flight = FuturesUnordered::new();
+            for (chain_number, chain) in chains.iter().enumerate() {
+                let candidate = if chain_number == 0 {
+                    initial_guess.clone()
+                } else {
+                    propose(chain, &mut rng)
+                };
+                in_flight.push(ctx.evaluate_tagged((chain_number, candidate.clone()), candidate));
+            }
+
+            let mut num_completed: usize = 0;
+
+            while let Some(((chain_number, candidate), obj_func_val)) = in_flight.next().await {
+                let temperature = schedule.temperature();
+                chains[chain_number].step(candidate, obj_func_val, temperature, &mut rng);
+
+                num_completed += 1;
+                if num_completed.is_multiple_of(num_chains) {
+                    schedule.on_iteration_completed(ctx, &mut chains);
+                    debug!(
+                        "{} evaluations completed at temperature {}. Seen: {:?}",
+                        num_completed, temperature, ctx.seen
+                    );
+                }
+
+                let candidate = propose(&chains[chain_number], &mut rng);
+                in_flight.push(ctx.evaluate_tagged((chain_number, candidate.clone()), candidate));
+            }
+        }
+    }
+}
+
+fn compute_temperature(
+    cooling_schedule: &CoolingSchedule,
+    start_temperature: f64,
+    iter_num: usize,
+) -> f64 {
+    match cooling_schedule {
+        CoolingSchedule::Exponential { decay_factor } => {
+            start_temperature * decay_factor.powi(iter_num as i32)
+        }
+        CoolingSchedule::Linear { decrement } => {
+            (start_temperature - decrement * iter_num as f64).max(0.0)
+        }
+        CoolingSchedule::Logarithmic => start_temperature / (1.0 + (iter_num as f64).ln_1p()),
+    }
+}
+
+fn accept(
+    current_obj_func_val: Option<f64>,
+    new_obj_func_val: Option<f64>,
+    temperature: f64,
+    rng: &mut StdRng,
+) -> bool {
+    match (current_obj_func_val, new_obj_func_val) {
+        (_, None) => false,
+        (None, Some(_)) => true,
+        (Some(current), Some(new)) => {
+            new <= current
+                || (temperature > 0.0 && rng.gen::<f64>() < ((current - new) / temperature).exp())
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::f64::consts::PI;
+
+pub(super) fn std_normal_pdf(z: f64) -> f64 {
+    (-0.5 * z * z).exp() / (2.0 * PI).sqrt()
+}
+
+pub(super) fn std_normal_cdf(z: f64) -> f64 {
+    0.5 * (1.0 + erf(z / 2.0_f64.sqrt()))
+}
+
+/// Abramowitz and Stegun 7.1.26, absolute error below 1.5e-7
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs());
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let result = 1.0 - poly * (-x * x).exp();
+    if x >= 0.0 {
+        result
+    } else {
+        -result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE: f64 = 1.5e-7;
+
+    #[test]
+    fn erf_matches_reference_values() {
+        for (x, expected) in [
+            (0.0, 0.0),
+            (0.5, 0.5204998778),
+            (1.0, 0.8427007929),
+            (2.0, 0.9953222650),
+            (4.0, 0.9999999846),
+        ] {
+            assert!((erf(x) - expected).abs() < TOLERANCE, "erf({})", x);
+            assert!((erf(-x) + expected).abs() < TOLERANCE, "erf({})", -x);
+        }
+    }
+
+    #[test]
+    fn std_normal_cdf_matches_reference_values() {
+        assert!((std_normal_cdf(0.0) - 0.5).abs() < TOLERANCE);
+        assert!((std_normal_cdf(1.959963985) - 0.975).abs() < TOLERANCE);
+        assert!((std_normal_cdf(-1.0) - 0.1586552539).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn std_normal_pdf_matches_reference_values() {
+        assert!((std_normal_pdf(0.0) - 0.3989422804).abs() < 1e-10);
+        assert!((std_normal_pdf(1.0) - std_normal_pdf(-1.0)).abs() < 1e-15);
+        assert!((std_normal_pdf(1.0) - 0.2419707245).abs() < 1e-10);
+    }
+}
@@ -0,0 +1,160 @@
+use super::ProcessingContext;
+use crate::algo::{AspirationCriterion, TabuAttribute, TabuSearchConf};
+use crate::param::{Dim, ParamsSpec, ParamsValue};
+use log::debug;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use serde_json::Number as NumberValue;
+use serde_json::Value::{self, Bool, Number, Object};
+use std::collections::VecDeque;
+
+struct Move {
+    name: String,
+    old_value: Value,
+    new_value: Value,
+}
+
+struct TabuEntry {
+    expiry_step: usize,
+    name: String,
+    value: Value,
+}
+
+/// The tabu list holds the changed dim and its previous value of each move made within the last
+/// `tabu_tenure` steps. If no admissible move succeeds, the search stays at the current candidate
+/// until tabu entries expire.
+pub(super) async fn run(ctx: &ProcessingContext, spec: ParamsSpec, algo_conf: TabuSearchConf) {
+    let initial_guess = spec.extract_initial_guess();
+
+    debug!("Starting with initial guess: {:?}", &initial_guess);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let mut best_obj_func_val = ctx.evaluate_one(Object(initial_guess.clone())).await;
+    let mut current = initial_guess;
+    let mut tabu_list: VecDeque<TabuEntry> = VecDeque::new();
+
+    for step in 1.. {
+        while tabu_list
+            .front()
+            .map(|entry| entry.expiry_step <= step)
+            .unwrap_or(false)
+        {
+            tabu_list.pop_front();
+        }
+
+        let mut moves: Vec<Move> = neighbourhood(&current, &spec, algo_conf.relative_step)
+            .into_iter()
+            .filter(|mv| {
+                algo_conf.aspiration != AspirationCriterion::Never
+                    || !is_tabu(mv, &tabu_list, algo_conf.tabu_attribute)
+            })
+            .collect();
+        moves.shuffle(&mut rng);
+        moves.truncate(algo_conf.degree_of_par.max(1));
+
+        let candidates = moves
+            .iter()
+            .map(|mv| {
+                let mut candidate = current.clone();
+                candidate.insert(mv.name.clone(), mv.new_value.clone());
+                Object(candidate)
+            })
+            .collect();
+        let obj_func_vals = ctx.evaluate(candidates).await;
+
+        let chosen = moves
+            .into_iter()
+            .zip(obj_func_vals)
+            .filter_map(|(mv, obj_func_val)| obj_func_val.map(|val| (mv, val)))
+            .filter(|(mv, obj_func_val)| {
+                let aspirates = algo_conf.aspiration == AspirationCriterion::ImprovesBest
+                    && best_obj_func_val
+                        .map(|best_obj_func_val| *obj_func_val < best_obj_func_val)
+                        .unwrap_or(true);
+                aspirates || !is_tabu(mv, &tabu_list, algo_conf.tabu_attribute)
+            })
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+        if let Some((mv, obj_func_val)) = chosen {
+            if best_obj_func_val
+                .map(|best_obj_func_val| obj_func_val < best_obj_func_val)
+                .unwrap_or(true)
+            {
+                best_obj_func_val = Some(obj_func_val);
+            }
+
+            current.insert(mv.name.clone(), mv.new_value);
+            tabu_list.push_back(TabuEntry {
+                expiry_step: step + algo_conf.tabu_tenure,
+                name: mv.name,
+                value: mv.old_value,
+            });
+        }
+
+        debug!(
+            "Step {} completed with {} tabu entries. Seen: {:?}",
+            step,
+            tabu_list.len(),
+            ctx.seen
+        );
+    }
+}
+
+fn is_tabu(mv: &Move, tabu_list: &VecDeque<TabuEntry>, tabu_attribute: TabuAttribute) -> bool {
+    tabu_list.iter().any(|entry| {
+        entry.name == mv.name
+            && (tabu_attribute == TabuAttribute::Dim || entry.value == mv.new_value)
+    })
+}
+
+fn neighbourhood(current: &ParamsValue, spec: &ParamsSpec, relative_step: f64) -> Vec<Move> {
+    let mut result = Vec::new();
+
+    for dim in &spec.dims {
+        match dim {
+            Dim::Boolean(dim_spec) => {
+                let value = current.get(&dim_spec.name).unwrap().as_bool().unwrap();
+                result.push(Move {
+                    name: dim_spec.name.clone(),
+                    old_value: Bool(value),
+                    new_value: Bool(!value),
+                });
+            }
+            Dim::RealNumber(dim_spec_with_bounds) => {
+                let name = &dim_spec_with_bounds.dim_spec.name;
+                let value = current.get(name).unwrap().as_f64().unwrap();
+                let min_value = dim_spec_with_bounds.min_value_incl;
+                let max_value = dim_spec_with_bounds.max_value_excl.max(min_value);
+                let step = relative_step * (max_value - min_value);
+
+                for new_value in [value - step, value + step] {
+                    let new_value = new_value.clamp(min_value, max_value);
+                    if new_value != value {
+                        result.push(Move {
+                            name: name.clone(),
+                            old_value: current.get(name).unwrap().clone(),
+                            new_value: Number(NumberValue::from_f64(new_value).unwrap()),
+                        });
+                    }
+                }
+            }
+            Dim::Integer(dim_spec_with_bounds) => {
+                let name = &dim_spec_with_bounds.dim_spec.name;
+                let value = current.get(name).unwrap().as_i64().unwrap();
+                let min_value = dim_spec_with_bounds.min_value_incl;
+                let max_value = dim_spec_with_bounds.max_value_excl.max(min_value);
+
+                for new_value in [value - 1, value + 1] {
+                    if (min_value..=max_value).contains(&new_value) {
+                        result.push(Move {
+                            name: name.clone(),
+                            old_value: Number(NumberValue::from(value)),
+                            new_value: Number(NumberValue::from(new_value)),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    result
+}
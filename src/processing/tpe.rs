@@ -0,0 +1,280 @@
+use super::stats::{std_normal_cdf, std_normal_pdf};
+use super::{random_candidate, sort_key, ProcessingContext};
+use crate::algo::TpeConf;
+use crate::param::{Dim, ParamsSpec, ParamsValue};
+use futures::stream::{FuturesUnordered, StreamExt};
+use log::debug;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+use serde_json::Number as NumberValue;
+use serde_json::Value::{Bool, Number, Object};
+
+const MAX_NUM_GOOD: usize = 25;
+const MAX_BANDWIDTH_DIVISOR: f64 = 100.0;
+const MAX_TRUNCATED_SAMPLE_ATTEMPTS: usize = 100;
+/// Keeps the densities of groups without observations, e.g. the bad group of the first
+/// suggestions, well-defined
+const MIN_PRIOR_WEIGHT: f64 = 1e-3;
+
+struct Observation {
+    candidate: ParamsValue,
+    obj_func_val: Option<f64>,
+}
+
+pub(super) async fn run(ctx: &ProcessingContext, spec: ParamsSpec, algo_conf: TpeConf) {
+    let initial_guess = spec.extract_initial_guess();
+
+    debug!("Starting with initial guess: {:?}", &initial_guess);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let mut observations: Vec<Observation> = ctx
+        .imported
+        .iter()
+        .map(|imported| Observation {
+            candidate: imported.candidate.clone(),
+            obj_func_val: imported.obj_func_val,
+        })
+        .collect();
+    let mut in_flight = FuturesUnordered::new();
+    // Imported observations count towards the startup candidates
+    let mut num_suggested = observations.len();
+
+    in_flight.push(evaluate(ctx, initial_guess));
+    num_suggested += 1;
+
+    loop {
+        // Migrants of the island model count as observations, like imported ones
+        observations.extend(ctx.migrants().into_iter().map(|migrant| Observation {
+            candidate: migrant.candidate,
+            obj_func_val: migrant.obj_func_val,
+        }));
+
+        while in_flight.len() < algo_conf.degree_of_par.max(1) {
+            let candidate = if num_suggested < algo_conf.num_startup_candidates {
+                random_candidate(&spec, &mut rng)
+            } else {
+                suggest(&spec, &observations, &algo_conf, &mut rng)
+            };
+            in_flight.push(evaluate(ctx, candidate));
+            num_suggested += 1;
+        }
+
+        if let Some(observation) = in_flight.next().await {
+            observations.push(observation);
+            debug!(
+                "{} observations completed. Seen: {:?}",
+                observations.len(),
+                ctx.seen
+            );
+        }
+    }
+}
+
+async fn evaluate(ctx: &ProcessingContext, candidate: ParamsValue) -> Observation {
+    let obj_func_val = ctx.evaluate_one(Object(candidate.clone())).await;
+    Observation {
+        candidate,
+        obj_func_val,
+    }
+}
+
+/// Suggests each dim independently. Failed evaluations count as bad observations.
+fn suggest(
+    spec: &ParamsSpec,
+    observations: &[Observation],
+    algo_conf: &TpeConf,
+    rng: &mut StdRng,
+) -> ParamsValue {
+    let mut ranking: Vec<&Observation> = observations.iter().collect();
+    ranking.sort_by(|a, b| {
+        sort_key(a.obj_func_val)
+            .partial_cmp(&sort_key(b.obj_func_val))
+            .unwrap()
+    });
+
+    let num_good = ((algo_conf.gamma * ranking.len() as f64).ceil() as usize)
+        .clamp(1, MAX_NUM_GOOD)
+        .min(ranking.len());
+    let (good, bad) = ranking.split_at(num_good);
+    let prior_weight = algo_conf.prior_weight.max(MIN_PRIOR_WEIGHT);
+
+    let mut result = ParamsValue::new();
+
+    for (dim_index, dim) in spec.dims.iter().enumerate() {
+        match dim {
+            Dim::Boolean(dim_spec) => {
+                let count_true = |group: &[&Observation]| {
+                    group
+                        .iter()
+                        .filter(|observation| {
+                            observation.candidate.get(&dim_spec.name).unwrap().as_bool()
+                                == Some(true)
+                        })
+                        .count() as f64
+                };
+                let prob_true = |group: &[&Observation]| {
+                    (count_true(group) + 0.5 * prior_weight) / (group.len() as f64 + prior_weight)
+                };
+                let good_prob_true = prob_true(good);
+                let bad_prob_true = prob_true(bad);
+
+                let ratio = |value: bool| {
+                    if value {
+                        good_prob_true / bad_prob_true
+                    } else {
+                        (1.0 - good_prob_true) / (1.0 - bad_prob_true)
+                    }
+                };
+
+                let value = (0..algo_conf.num_ei_candidates.max(1))
+                    .map(|_| rng.gen_bool(good_prob_true))
+                    .max_by(|a, b| ratio(*a).partial_cmp(&ratio(*b)).unwrap())
+                    .unwrap();
+                result.insert(dim_spec.name.clone(), Bool(value));
+            }
+            Dim::RealNumber(_) | Dim::Integer(_) => {
+                let num_bins = match dim {
+                    Dim::Integer(dim_spec_with_bounds) => Some(
+                        dim_spec_with_bounds.max_value_excl - dim_spec_with_bounds.min_value_incl,
+                    ),
+                    _ => None,
+                };
+
+                let coords = |group: &[&Observation]| -> Vec<f64> {
+                    group
+                        .iter()
+                        .map(|observation| spec.to_unit_cube(&observation.candidate)[dim_index])
+                        .collect()
+                };
+                let good_estimator = ParzenEstimator::new(&coords(good), prior_weight);
+                let bad_estimator = ParzenEstimator::new(&coords(bad), prior_weight);
+
+                let coord = (0..algo_conf.num_ei_candidates.max(1))
+                    .map(|_| {
+                        let coord = snap_coord(good_estimator.sample(rng), num_bins);
+                        let ratio = good_estimator.density(coord, num_bins)
+                            / bad_estimator.density(coord, num_bins);
+                        (ratio, coord)
+                    })
+                    .max_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap())
+                    .map(|(_, coord)| coord)
+                    .unwrap();
+
+                match dim {
+                    Dim::Integer(dim_spec_with_bounds) => {
+                        let value = dim_spec_with_bounds.min_value_incl
+                            + (coord * num_bins.unwrap() as f64).round() as i64;
+                        result.insert(
+                            dim_spec_with_bounds.dim_spec.name.clone(),
+                            Number(NumberValue::from(value)),
+                        );
+                    }
+                    Dim::RealNumber(dim_spec_with_bounds) => {
+                        let value = dim_spec_with_bounds.min_value_incl
+                            + coord
+                                * (dim_spec_with_bounds.max_value_excl
+                                    - dim_spec_with_bounds.min_value_incl);
+                        result.insert(
+                            dim_spec_with_bounds.dim_spec.name.clone(),
+                            Number(NumberValue::from_f64(value).unwrap()),
+                        );
+                    }
+                    Dim::Boolean(_) => unreachable!(),
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Rounds a unit interval coordinate to the nearest of `num_bins + 1` equidistant lattice points,
+/// if given.
+fn snap_coord(coord: f64, num_bins: Option<i64>) -> f64 {
+    match num_bins {
+        Some(num_bins) if num_bins > 0 => (coord * num_bins as f64).round() / num_bins as f64,
+        _ => coord,
+    }
+}
+
+/// Mixture of Gaussians truncated to the unit interval, one per observation plus a broad prior
+/// component. Bandwidths are the larger distance to the neighbouring observations.
+struct ParzenEstimator {
+    components: Vec<(f64, f64, f64)>,
+}
+
+impl ParzenEstimator {
+    fn new(coords: &[f64], prior_weight: f64) -> ParzenEstimator {
+        let mut sorted_coords = coords.to_vec();
+        sorted_coords.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let min_bandwidth = 1.0 / (sorted_coords.len() as f64 + 1.0).min(MAX_BANDWIDTH_DIVISOR);
+
+        let mut components: Vec<(f64, f64, f64)> = sorted_coords
+            .iter()
+            .enumerate()
+            .map(|(index, &coord)| {
+                let lower_neighbour = if index > 0 {
+                    sorted_coords[index - 1]
+                } else {
+                    0.0
+                };
+                let upper_neighbour = sorted_coords.get(index + 1).cloned().unwrap_or(1.0);
+                let bandwidth = (coord - lower_neighbour)
+                    .max(upper_neighbour - coord)
+                    .clamp(min_bandwidth, 1.0);
+                (coord, bandwidth, 1.0)
+            })
+            .collect();
+
+        components.push((0.5, 1.0, prior_weight));
+
+        ParzenEstimator { components }
+    }
+
+    fn total_weight(&self) -> f64 {
+        self.components.iter().map(|(_, _, weight)| weight).sum()
+    }
+
+    fn sample(&self, rng: &mut StdRng) -> f64 {
+        let mut threshold = rng.gen::<f64>() * self.total_weight();
+        let (mean, bandwidth, _) = self
+            .components
+            .iter()
+            .find(|(_, _, weight)| {
+                threshold -= weight;
+                threshold <= 0.0
+            })
+            .unwrap_or(self.components.last().unwrap());
+
+        let normal = Normal::new(*mean, *bandwidth).unwrap();
+        (0..MAX_TRUNCATED_SAMPLE_ATTEMPTS)
+            .map(|_| normal.sample(rng))
+            .find(|sample| (0.0..=1.0).contains(sample))
+            .unwrap_or(*mean)
+    }
+
+    /// Density at the coordinate, or the probability mass of the lattice cell around it if
+    /// `num_bins` is given
+    fn density(&self, coord: f64, num_bins: Option<i64>) -> f64 {
+        let half_width = match num_bins {
+            Some(num_bins) if num_bins > 0 => 0.5 / num_bins as f64,
+            _ => 0.0,
+        };
+
+        self.components
+            .iter()
+            .map(|(mean, bandwidth, weight)| {
+                let cdf = |x: f64| std_normal_cdf((x - mean) / bandwidth);
+                let normalization = (cdf(1.0) - cdf(0.0)).max(f64::MIN_POSITIVE);
+                let component_density = if half_width > 0.0 {
+                    cdf(coord + half_width) - cdf(coord - half_width)
+                } else {
+                    std_normal_pdf((coord - mean) / bandwidth) / bandwidth
+                };
+                weight * component_density / normalization
+            })
+            .sum::<f64>()
+            / self.total_weight()
+    }
+}
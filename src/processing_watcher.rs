@@ -1,26 +1,54 @@
 use std::{collections::VecDeque, time::Duration};
 
 use crate::{
-    app_config::{BEST_SEEN_TABLE_SIZE_HINT, CANDIDATE_WINDOW_LENGTH_HINT},
-    domain::{CandidateEvalReport, ProcessingState, StatusMessage},
+    algo::AlgoConf,
+    app_config::{BEST_SEEN_TABLE_SIZE_HINT, CANDIDATE_WINDOW_LENGTH_HINT, PARETO_FRONT_SIZE_HINT},
+    domain::{
+        CandidateEvalReport, ObjectiveConf, ObjectiveDirection, ProcessingState, RestartReport,
+        StatusMessage, StepSizeReport,
+    },
+    param::ParamsSpec,
+    pareto,
     type_aliases::AppTime,
 };
+use serde_json::Value::Object;
 
 #[derive(Debug)]
 pub struct ProcessingWatcher {
     pub start_time: AppTime,
     pub last_time: f64,
+    algo_conf: AlgoConf,
+    direction: ObjectiveDirection,
+    min_samples: usize,
     eval_report_queue: VecDeque<CandidateEvalReport>,
     best_seen_reports: Vec<CandidateEvalReport>,
+    island_best_seen_reports: Vec<Vec<CandidateEvalReport>>,
+    pareto_front_reports: Vec<CandidateEvalReport>,
+    step_size_reports: Vec<StepSizeReport>,
+    restart_reports: Vec<RestartReport>,
 }
 
 impl ProcessingWatcher {
-    pub fn new(time: AppTime) -> ProcessingWatcher {
+    pub fn new(
+        time: AppTime,
+        algo_conf: AlgoConf,
+        objective_conf: &ObjectiveConf,
+    ) -> ProcessingWatcher {
         ProcessingWatcher {
             start_time: time,
             last_time: 0.0,
+            algo_conf,
+            direction: objective_conf.direction,
+            min_samples: objective_conf
+                .noise_handling
+                .map(|noise_handling| noise_handling.min_samples)
+                .unwrap_or(1),
             eval_report_queue: VecDeque::new(),
             best_seen_reports: Vec::with_capacity(BEST_SEEN_TABLE_SIZE_HINT),
+            island_best_seen_reports: Vec::new(),
+            pareto_front_reports: Vec::new(),
+            step_size_reports: Vec::new(),
+            restart_reports: Vec::new(),
         }
     }
 
@@ -32,43 +60,177 @@ impl ProcessingWatcher {
     }
 
     pub fn on_delegate_status_msg(&mut self, message: &StatusMessage) {
-        match message {
-            StatusMessage::CandidateEvalReport(report) => {
-                self.eval_report_queue.push_back(report.clone());
-
-                if let Some(obj_func_val) = report.obj_func_val {
-                    if self.best_seen_reports.len() < BEST_SEEN_TABLE_SIZE_HINT
-                        || obj_func_val
-                            < self.best_seen_reports.last().unwrap().obj_func_val.unwrap()
-                    {
-                        self.best_seen_reports.push(report.clone());
-                        self.best_seen_reports.sort_by(|a, b| {
-                            a.obj_func_val
-                                .unwrap()
-                                .partial_cmp(&b.obj_func_val.unwrap())
-                                .unwrap()
-                        });
-                        if self.best_seen_reports.len() > BEST_SEEN_TABLE_SIZE_HINT {
-                            self.best_seen_reports.pop();
-                        }
-                    }
-                }
+        if let StatusMessage::StepSizeReport(report) = message {
+            self.step_size_reports.push(report.clone());
+        }
+
+        if let StatusMessage::RestartReport(report) = message {
+            self.restart_reports.push(report.clone());
+        }
+
+        if let StatusMessage::EffectiveAlgoConf(algo_conf) = message {
+            self.algo_conf = algo_conf.clone();
+        }
+
+        if let StatusMessage::CandidateEvalReport(report) = message {
+            self.on_candidate_eval_report(report);
+        }
+    }
+
+    /// The algo conf in effect, including updates applied while processing
+    pub fn algo_conf(&self) -> &AlgoConf {
+        &self.algo_conf
+    }
+
+    /// Adds the reports of an earlier run whose candidates the spec contains to the history,
+    /// marked as imported, like the processing job does. Their island and cache hit refer to the
+    /// earlier run and are cleared.
+    pub fn import(&mut self, spec: &ParamsSpec, reports: &[CandidateEvalReport]) {
+        for report in reports {
+            if !matches!(&report.candidate, Object(candidate) if spec.contains(candidate)) {
+                continue;
             }
-            _ => (),
-        };
+            self.on_candidate_eval_report(&CandidateEvalReport {
+                imported: true,
+                island: None,
+                cache_hit: false,
+                ..report.clone()
+            });
+        }
+    }
+
+    /// All reports of the processing job, imported ones first
+    pub fn candidate_eval_reports(&self) -> Vec<CandidateEvalReport> {
+        self.eval_report_queue.iter().cloned().collect()
+    }
+
+    fn on_candidate_eval_report(&mut self, report: &CandidateEvalReport) {
+        self.eval_report_queue.push_back(report.clone());
+
+        update_best_seen_reports(
+            &mut self.best_seen_reports,
+            report,
+            self.direction,
+            self.min_samples,
+        );
+
+        if let Some(island) = report.island {
+            if self.island_best_seen_reports.len() <= island {
+                self.island_best_seen_reports.resize(island + 1, Vec::new());
+            }
+            update_best_seen_reports(
+                &mut self.island_best_seen_reports[island],
+                report,
+                self.direction,
+                self.min_samples,
+            );
+        }
+
+        if report.obj_func_vals.is_some() && report.feasible {
+            self.update_pareto_front(report);
+        }
     }
 
     pub fn compute_processing_state(&self) -> ProcessingState {
         ProcessingState {
-            recent_candidate_eval_reports: self
-                .eval_report_queue
-                .iter()
-                .map(|report| report.clone())
-                .collect(),
+            algo_conf: self.algo_conf.clone(),
+            recent_candidate_eval_reports: self.eval_report_queue.iter().cloned().collect(),
             best_seen_candidate_eval_reports: self.best_seen_reports.clone(),
+            island_best_seen_candidate_eval_reports: self.island_best_seen_reports.clone(),
+            pareto_front_candidate_eval_reports: self.pareto_front_reports.clone(),
+            step_size_reports: self.step_size_reports.clone(),
+            restart_reports: self.restart_reports.clone(),
             time: self.last_time,
             window_length_hint: CANDIDATE_WINDOW_LENGTH_HINT,
             best_seen_table_size_hint: BEST_SEEN_TABLE_SIZE_HINT,
+            pareto_front_size_hint: PARETO_FRONT_SIZE_HINT,
         }
     }
+
+    /// Maintains the archive of non-dominated multi-objective reports. Beyond the size hint, the
+    /// most crowded member is dropped, which keeps the archive spread along the front.
+    fn update_pareto_front(&mut self, report: &CandidateEvalReport) {
+        let direction = self.direction;
+        let new_vals = minimized_obj_func_vals(report, direction);
+
+        if self.pareto_front_reports.iter().any(|member| {
+            let member_vals = minimized_obj_func_vals(member, direction);
+            member_vals == new_vals || pareto::dominates(&member_vals, &new_vals)
+        }) {
+            return;
+        }
+
+        self.pareto_front_reports.retain(|member| {
+            !pareto::dominates(&new_vals, &minimized_obj_func_vals(member, direction))
+        });
+        self.pareto_front_reports.push(report.clone());
+
+        if self.pareto_front_reports.len() > PARETO_FRONT_SIZE_HINT {
+            let member_vals: Vec<Vec<f64>> = self
+                .pareto_front_reports
+                .iter()
+                .map(|member| minimized_obj_func_vals(member, direction))
+                .collect();
+            let points: Vec<&[f64]> = member_vals.iter().map(Vec::as_slice).collect();
+            let distances = pareto::crowding_distances(&points);
+            let most_crowded = (0..distances.len())
+                .min_by(|&a, &b| distances[a].partial_cmp(&distances[b]).unwrap())
+                .unwrap();
+            self.pareto_front_reports.remove(most_crowded);
+        }
+    }
+}
+
+fn update_best_seen_reports(
+    best_seen_reports: &mut Vec<CandidateEvalReport>,
+    report: &CandidateEvalReport,
+    direction: ObjectiveDirection,
+    min_samples: usize,
+) {
+    if let Some(obj_func_val) = ranking_obj_func_val(report).filter(|_| report.feasible) {
+        if report.num_samples.is_some() {
+            // Under noise handling, a candidate is listed with its latest mean only, once it has
+            // enough samples to be the incumbent
+            best_seen_reports
+                .retain(|best_seen_report| best_seen_report.candidate != report.candidate);
+        }
+
+        let sufficiently_sampled = report.num_samples.unwrap_or(1) >= min_samples;
+
+        if sufficiently_sampled
+            && (best_seen_reports.len() < BEST_SEEN_TABLE_SIZE_HINT
+                || direction.is_better(
+                    obj_func_val,
+                    ranking_obj_func_val(best_seen_reports.last().unwrap()).unwrap(),
+                ))
+        {
+            best_seen_reports.push(report.clone());
+            best_seen_reports.sort_by(|a, b| {
+                direction
+                    .to_minimized(ranking_obj_func_val(a).unwrap())
+                    .partial_cmp(&direction.to_minimized(ranking_obj_func_val(b).unwrap()))
+                    .unwrap()
+            });
+            if best_seen_reports.len() > BEST_SEEN_TABLE_SIZE_HINT {
+                best_seen_reports.pop();
+            }
+        }
+    }
+}
+
+/// The mean under noise handling, the single sample otherwise
+fn ranking_obj_func_val(report: &CandidateEvalReport) -> Option<f64> {
+    report.mean_obj_func_val.or(report.obj_func_val)
+}
+
+fn minimized_obj_func_vals(
+    report: &CandidateEvalReport,
+    direction: ObjectiveDirection,
+) -> Vec<f64> {
+    report
+        .obj_func_vals
+        .iter()
+        .flatten()
+        .map(|val| direction.to_minimized(val.value))
+        .collect()
 }